@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use crate::diagnostic::{Diagnostic, Location, codes};
+use crate::parser::{Call, Declaration, Literal, Program, Value};
+use crate::span::LineIndex;
+use crate::stratify::DependencyGraph;
+
+// Смысл отрицания в телах правил
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Semantics {
+    // Программа разбивается на страты; отрицание внутри цикла — ошибка
+    #[default]
+    Stratified,
+    // Обоснованная (well-founded) модель через чередующуюся неподвижную точку:
+    // любая программа вычисляется, а факты, зависящие от себя через отрицание,
+    // получают значение undefined
+    WellFounded,
+}
+
+// Сигнатура предиката: арность и позиция первого использования
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub arity: usize,
+    pub location: Location,
+}
+
+// Таблица сигнатур всех предикатов программы
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Signatures {
+    predicates: BTreeMap<String, Signature>,
+}
+
+impl Signatures {
+    pub fn get(&self, func: &str) -> Option<&Signature> {
+        self.predicates.get(func)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Signature)> {
+        self.predicates
+            .iter()
+            .map(|(func, signature)| (func.as_str(), signature))
+    }
+}
+
+// Семантический анализ после parse_program: возвращает все найденные ошибки.
+// Стратифицируемость проверяется только для стратифицированной семантики:
+// обоснованная модель есть у любой программы.
+pub fn analyze(program: &Program, semantics: Semantics) -> Result<Signatures, Vec<Diagnostic>> {
+    let mut errors = check_safety(program);
+    if semantics == Semantics::Stratified {
+        errors.extend(check_stratification(program));
+    }
+    match check_arities(program) {
+        Ok(signatures) if errors.is_empty() => Ok(signatures),
+        Ok(_) => Err(errors),
+        Err(mut arity) => {
+            arity.extend(errors);
+            Err(arity)
+        }
+    }
+}
+
+// Отрицание не должно встречаться внутри рекурсии: о первом таком цикле сообщается
+// с подписью у каждого его ребра
+pub fn check_stratification(program: &Program) -> Vec<Diagnostic> {
+    match DependencyGraph::new(program).stratify() {
+        Ok(_) => Vec::new(),
+        Err(error) => vec![error],
+    }
+}
+
+// Ограниченность переменных (range restriction): каждая переменная заключения
+// и отрицательных литералов должна встречаться в положительном литерале тела.
+// Иначе вычисление снизу вверх дало бы бесконечно много или неопределённые факты.
+// Каждая такая переменная сообщается один раз для литерала, в позиции её первого вхождения.
+pub fn check_safety(program: &Program) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+    for declaration in &program.declarations {
+        let Declaration::Conclusion { left, right, .. } = declaration else {
+            continue;
+        };
+
+        let bound = |var: &str| {
+            right.iter().any(|literal| {
+                !literal.is_negative()
+                    && literal
+                        .call
+                        .args
+                        .iter()
+                        .any(|arg| matches!(arg, Value::Variable(v) if v == var))
+            })
+        };
+
+        for (var, location) in unbound_variables(left, &bound, &program.lines) {
+            errors.push(
+                Diagnostic::error(
+                    codes::UNBOUND_VARIABLE,
+                    format!(
+                        "Variable '{}' in conclusion {} is not bound by a positive literal in its body",
+                        var, left
+                    ),
+                    location,
+                )
+                .with_help(format!("add a positive literal that uses '{}' to the body", var)),
+            );
+        }
+        for literal in right.iter().filter(|literal| literal.is_negative()) {
+            for (var, location) in unbound_variables(&literal.call, &bound, &program.lines) {
+                errors.push(
+                    Diagnostic::error(
+                        codes::UNBOUND_VARIABLE,
+                        format!(
+                            "Variable '{}' in {} is not bound by a positive literal",
+                            var,
+                            Literal::negative(literal.call.clone())
+                        ),
+                        location,
+                    )
+                    .with_help(format!(
+                        "negated literals only filter bindings; bind '{}' with a positive literal",
+                        var
+                    )),
+                );
+            }
+        }
+    }
+    errors
+}
+
+// Различные переменные вызова, не удовлетворяющие bound, с позициями первого вхождения
+fn unbound_variables(
+    call: &Call,
+    bound: &impl Fn(&str) -> bool,
+    lines: &LineIndex,
+) -> Vec<(String, Location)> {
+    let mut unbound: Vec<(String, Location)> = Vec::new();
+    for (i, arg) in call.args.iter().enumerate() {
+        if let Value::Variable(var) = arg
+            && !bound(var)
+            && !unbound.iter().any(|(seen, _)| seen == var)
+        {
+            let span = call.arg_spans.get(i).copied().unwrap_or(call.span);
+            unbound.push((var.clone(), lines.location(span)));
+        }
+    }
+    unbound
+}
+
+// Арность предиката фиксируется первым использованием (в порядке текста программы:
+// объявления, заключения и тела правил, запросы); каждое использование с другой
+// арностью — ошибка
+pub fn check_arities(program: &Program) -> Result<Signatures, Vec<Diagnostic>> {
+    let mut signatures = Signatures::default();
+    let mut errors = Vec::new();
+    let lines = &program.lines;
+
+    for declaration in &program.declarations {
+        match declaration {
+            Declaration::Declare { fact, .. } => signatures.record_call(fact, lines, &mut errors),
+            Declaration::Conclusion { left, right, .. } => {
+                signatures.record_call(left, lines, &mut errors);
+                for literal in right {
+                    signatures.record_call(&literal.call, lines, &mut errors);
+                }
+            }
+            Declaration::Query { goal, .. } => signatures.record_call(goal, lines, &mut errors),
+            Declaration::Vars { .. } | Declaration::Error { .. } => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(signatures)
+    } else {
+        Err(errors)
+    }
+}
+
+impl Signatures {
+    // Позиция использования — имя функции в начале вызова
+    fn record_call(&mut self, call: &Call, lines: &LineIndex, errors: &mut Vec<Diagnostic>) {
+        let (line, column) = lines.line_col(call.span.start);
+        let signature = Signature {
+            arity: call.args.len(),
+            location: Location::new(line, column, call.func.chars().count()),
+        };
+        self.record(&call.func, signature, errors);
+    }
+
+    fn record(&mut self, func: &str, used: Signature, errors: &mut Vec<Diagnostic>) {
+        let Some(first) = self.predicates.get(func) else {
+            self.predicates.insert(func.to_string(), used);
+            return;
+        };
+        if first.arity != used.arity {
+            errors.push(
+                Diagnostic::error(
+                    codes::ARITY_MISMATCH,
+                    format!(
+                        "Predicate {} is used with {} argument(s), but its first use has {}",
+                        func, used.arity, first.arity
+                    ),
+                    used.location,
+                )
+                .with_label(
+                    first.location,
+                    format!("first use of {} with {} argument(s)", func, first.arity),
+                ),
+            );
+        }
+    }
+}
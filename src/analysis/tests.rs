@@ -0,0 +1,121 @@
+use crate::analysis::{Semantics, analyze, check_arities, check_safety};
+use crate::config::LanguageConfig;
+use crate::diagnostic::{Location, codes};
+use crate::lexer::Lexer;
+use crate::parser::{Parser, Program};
+
+fn parse(input: &str) -> Program {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    parser.parse_program().expect("parsing failed")
+}
+
+#[test]
+fn test_arity_signatures_of_consistent_program() {
+    let program = parse("declare Q(Ann); conclusion A(x,y):-Q(x),Q(y); ?- A(x, Ann)");
+    let signatures = check_arities(&program).expect("expected signatures");
+    assert_eq!(signatures.get("Q").map(|s| s.arity), Some(1));
+    assert_eq!(signatures.get("A").map(|s| s.arity), Some(2));
+    assert_eq!(signatures.iter().count(), 2);
+}
+
+#[test]
+fn test_arity_error_against_declaration() {
+    let program = parse("declare Q(Name);\nconclusion Q(x,y):-B(z)");
+    let errors = check_arities(&program).expect_err("expected arity errors");
+    assert_eq!(errors.len(), 1);
+    let error = &errors[0];
+    assert_eq!(
+        error.message,
+        "Predicate Q is used with 2 argument(s), but its first use has 1"
+    );
+    assert_eq!(error.code, codes::ARITY_MISMATCH);
+    assert_eq!(error.location, Location::new(2, 12, 1));
+    assert_eq!(error.labels.len(), 1);
+    assert_eq!(error.labels[0].location, Location::new(1, 9, 1));
+    assert_eq!(error.labels[0].message, "first use of Q with 1 argument(s)");
+}
+
+#[test]
+fn test_arity_reports_every_mismatch() {
+    let program = parse("conclusion A(x):-B(x),B(x,y); conclusion A(x,y):-B(x); ?- B(x,y,z)");
+    let errors = check_arities(&program).expect_err("expected arity errors");
+    let positions: Vec<_> = errors
+        .iter()
+        .map(|e| (e.location.line, e.location.column))
+        .collect();
+    assert_eq!(positions, vec![(1, 23), (1, 42), (1, 59)]);
+    let first_uses: Vec<_> = errors
+        .iter()
+        .map(|e| {
+            e.labels
+                .first()
+                .map(|l| (l.location.line, l.location.column))
+        })
+        .collect();
+    assert_eq!(
+        first_uses,
+        vec![Some((1, 18)), Some((1, 12)), Some((1, 18))]
+    );
+}
+
+#[test]
+fn test_safety_accepts_range_restricted_rules() {
+    let program = parse("declare Q(Ann); conclusion A(x,Ann):-Q(x),not B(x)");
+    assert!(check_safety(&program).is_empty());
+}
+
+#[test]
+fn test_safety_error_unbound_head_variable() {
+    let program = parse("declare B(Ann);\nconclusion A(x,y):-B(x)");
+    let errors = check_safety(&program);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "Variable 'y' in conclusion A(x, y) is not bound by a positive literal in its body"
+    );
+    assert_eq!(errors[0].code, codes::UNBOUND_VARIABLE);
+    assert_eq!(errors[0].location, Location::new(2, 16, 1));
+    assert!(errors[0].help.is_some());
+}
+
+#[test]
+fn test_safety_error_variable_only_under_negation() {
+    let program = parse("conclusion A(x,y):-Q(x),not B(x,y),not Q(z)");
+    let errors = check_safety(&program);
+    let reported: Vec<_> = errors
+        .iter()
+        .map(|e| (e.message.split('\'').nth(1).unwrap(), e.location.column))
+        .collect();
+    // y в заключении, y в 'not B(x, y)', z в 'not Q(z)'
+    assert_eq!(reported, vec![("y", 16), ("y", 33), ("z", 42)]);
+    assert!(errors[1].message.contains("in not B(x, y)"));
+}
+
+#[test]
+fn test_analyze_collects_arity_and_safety_errors() {
+    let program = parse("declare Q(Ann); conclusion A(x,y):-Q(x,x)");
+    let errors = analyze(&program, Semantics::Stratified).expect_err("expected errors");
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn test_analyze_reports_negation_cycle_only_when_stratified() {
+    let program = parse("declare Q(Ann); conclusion A(x):-Q(x),not B(x); conclusion B(x):-A(x)");
+    let errors = analyze(&program, Semantics::Stratified).expect_err("expected errors");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, codes::NOT_STRATIFIABLE);
+    assert!(analyze(&program, Semantics::WellFounded).is_ok());
+}
+
+#[test]
+fn test_arity_of_multi_argument_fact() {
+    let program = parse("declare A(Alpha, Beta);\nconclusion Q(x):-A(x)");
+    let errors = check_arities(&program).expect_err("expected arity errors");
+    assert_eq!(
+        errors[0].message,
+        "Predicate A is used with 1 argument(s), but its first use has 2"
+    );
+    assert!(check_arities(&parse("declare A(Alpha, Beta); conclusion Q(x):-A(x, y)")).is_ok());
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+// Какие слова допустимы как имена предикатов
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicates {
+    // Только перечисленные имена, в порядке перечисления
+    Named(Vec<String>),
+    // Любое слово с заглавной буквы
+    Capitalised,
+}
+
+// Какие слова в позиции аргумента считаются переменными; остальные — константы
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Variables {
+    // Только перечисленные слова
+    Fixed(Vec<String>),
+    // Как в Прологе: слово с заглавной буквы или с '_'; '_' — анонимная переменная
+    PrologStyle,
+    // Слова, объявленные в программе через 'vars x, y, w' до их использования
+    Declared,
+}
+
+impl Variables {
+    // Набор переменных по умолчанию: x, y и z
+    pub fn default_fixed() -> Self {
+        Variables::Fixed(vec!["x".into(), "y".into(), "z".into()])
+    }
+}
+
+// Настройки языка, которые парсер получает при создании.
+// По умолчанию предикаты — Q, B и A без ограничения арности, переменные — x, y и z,
+// у каждого атома есть хотя бы один аргумент.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageConfig {
+    predicates: Predicates,
+    arities: BTreeMap<String, usize>,
+    variables: Variables,
+    zero_arity: bool,
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        Self {
+            predicates: Predicates::Named(vec!["Q".into(), "B".into(), "A".into()]),
+            arities: BTreeMap::new(),
+            variables: Variables::default_fixed(),
+            zero_arity: false,
+        }
+    }
+}
+
+impl LanguageConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn predicates<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.predicates = Predicates::Named(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn any_capitalised(mut self) -> Self {
+        self.predicates = Predicates::Capitalised;
+        self
+    }
+
+    // Фиксированная арность предиката; у предикатов без неё арность не проверяется
+    pub fn arity(mut self, name: impl Into<String>, arity: usize) -> Self {
+        self.arities.insert(name.into(), arity);
+        self
+    }
+
+    pub fn variables(mut self, variables: Variables) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    // Атомы без аргументов: 'Ready()' или просто 'Ready' — в фактах, правилах и запросах
+    pub fn zero_arity(mut self, allowed: bool) -> Self {
+        self.zero_arity = allowed;
+        self
+    }
+
+    pub fn allows_zero_arity(&self) -> bool {
+        self.zero_arity
+    }
+
+    pub fn variable_convention(&self) -> &Variables {
+        &self.variables
+    }
+
+    // Является ли слово переменной; declared — имена из уже разобранных 'vars'
+    pub fn is_variable(&self, word: &str, declared: &[String]) -> bool {
+        match &self.variables {
+            Variables::Fixed(names) => names.iter().any(|n| n == word),
+            Variables::PrologStyle => word
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_uppercase() || c == '_'),
+            Variables::Declared => declared.iter().any(|n| n == word),
+        }
+    }
+
+    pub fn allows(&self, name: &str) -> bool {
+        match &self.predicates {
+            Predicates::Named(names) => names.iter().any(|n| n == name),
+            Predicates::Capitalised => name.chars().next().is_some_and(char::is_uppercase),
+        }
+    }
+
+    pub fn arity_of(&self, name: &str) -> Option<usize> {
+        self.arities.get(name).copied()
+    }
+
+    // Описание допустимых имён для сообщений об ошибках: "Q, B or A"
+    pub fn describe_predicates(&self) -> String {
+        match &self.predicates {
+            Predicates::Named(names) => match names.split_last() {
+                None => "none configured".to_string(),
+                Some((last, [])) => last.clone(),
+                Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            },
+            Predicates::Capitalised => "any capitalised word".to_string(),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::config::{LanguageConfig, Variables};
+
+#[test]
+fn test_config_default_predicates() {
+    let config = LanguageConfig::default();
+    assert!(config.allows("Q") && config.allows("B") && config.allows("A"));
+    assert!(!config.allows("Parent"));
+    assert_eq!(config.arity_of("A"), None);
+    assert_eq!(config.describe_predicates(), "Q, B or A");
+}
+
+#[test]
+fn test_config_named_predicates() {
+    let config = LanguageConfig::new()
+        .predicates(["Parent", "Person"])
+        .arity("Parent", 2);
+    assert!(config.allows("Person"));
+    assert!(!config.allows("Q"));
+    assert_eq!(config.arity_of("Parent"), Some(2));
+    assert_eq!(config.arity_of("Person"), None);
+    assert_eq!(config.describe_predicates(), "Parent or Person");
+}
+
+#[test]
+fn test_config_any_capitalised() {
+    let config = LanguageConfig::new().any_capitalised();
+    assert!(config.allows("Ancestor"));
+    assert!(config.allows("Ж"));
+    assert!(!config.allows("ancestor"));
+    assert_eq!(config.describe_predicates(), "any capitalised word");
+}
+
+#[test]
+fn test_config_variable_conventions() {
+    let fixed = LanguageConfig::default();
+    assert!(fixed.is_variable("x", &[]));
+    assert!(!fixed.is_variable("w", &[]));
+
+    let prolog = LanguageConfig::new().variables(Variables::PrologStyle);
+    assert!(prolog.is_variable("Person", &[]));
+    assert!(prolog.is_variable("_rest", &[]));
+    assert!(!prolog.is_variable("ann", &[]));
+
+    let declared = LanguageConfig::new().variables(Variables::Declared);
+    assert!(declared.is_variable("w", &["w".to_string()]));
+    assert!(!declared.is_variable("x", &[]));
+}
+
+#[test]
+fn test_config_zero_arity() {
+    assert!(!LanguageConfig::default().allows_zero_arity());
+    assert!(LanguageConfig::new().zero_arity(true).allows_zero_arity());
+}
@@ -0,0 +1,363 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Write;
+
+use crate::config::LanguageConfig;
+use crate::diagnostic::Diagnostic;
+use crate::lexer::{Lexem, LexemKind, source_text};
+use crate::parser::{ANONYMOUS_VAR, Call, Declaration, Literal, Polarity, Program, Value};
+use crate::span::{LineIndex, Span};
+
+// Вид узла конкретного синтаксического дерева; лексемы лежат в узлах как есть
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Program,
+    Declare,
+    Conclusion,
+    Query,
+    Vars,
+    Literal,
+    Call,
+    // Сломанная декларация: лексемы, пропущенные при восстановлении после ошибки
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(Lexem),
+}
+
+// Узел дерева без потерь: все лексемы вместе с пробелами вокруг них,
+// поэтому текст дерева совпадает с исходным текстом байт в байт.
+// Дочерние узлы Program — декларации; между ними лежат ';', лишние лексемы и конец файла.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    pub fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> Self {
+        Self { kind, children }
+    }
+
+    // Лексемы узла в порядке текста
+    pub fn tokens(&self) -> Vec<&Lexem> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    // Дочерние узлы (без лексем)
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    // Байты узла без пробелов по краям
+    pub fn span(&self) -> Span {
+        let tokens = self.tokens();
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::default(),
+        }
+    }
+
+    // Текст узла вместе с пробелами
+    pub fn text(&self) -> String {
+        source_text(self.tokens())
+    }
+
+    // Отладочное представление: узел и его лексемы с отступом в два пробела на уровень
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        self.write_tree(&mut out, 0);
+        out
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a Lexem>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    fn write_tree(&self, out: &mut String, depth: usize) {
+        let span = self.span();
+        let _ = writeln!(
+            out,
+            "{}{:?}@{}..{}",
+            "  ".repeat(depth),
+            self.kind,
+            span.start,
+            span.end
+        );
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.write_tree(out, depth + 1),
+                SyntaxElement::Token(token) => {
+                    let _ = writeln!(
+                        out,
+                        "{}{:?}@{}..{}",
+                        "  ".repeat(depth + 1),
+                        token.text(),
+                        token.span.start,
+                        token.span.end
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+// Результат разбора без потерь: дерево, полученная из него программа и ошибки
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxOutcome {
+    pub tree: SyntaxNode,
+    pub program: Program,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Построение дерева по ходу разбора. Лексемы добавляются подряд; разобрав конструкцию,
+// парсер оборачивает в узел всё, что было добавлено после её начала (checkpoint).
+#[derive(Debug, Default)]
+pub(crate) struct TreeBuilder {
+    children: Vec<SyntaxElement>,
+}
+
+impl TreeBuilder {
+    pub(crate) fn token(&mut self, lexem: Lexem) {
+        self.children.push(SyntaxElement::Token(lexem));
+    }
+
+    pub(crate) fn checkpoint(&self) -> usize {
+        self.children.len()
+    }
+
+    pub(crate) fn wrap(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        let children = self.children.split_off(checkpoint);
+        self.children
+            .push(SyntaxElement::Node(SyntaxNode::new(kind, children)));
+    }
+
+    // Узел Error на месте сломанной конструкции: уже построенные в ней узлы
+    // разбираются, остаются только лексемы
+    pub(crate) fn wrap_error(&mut self, checkpoint: usize) {
+        let mut tokens = Vec::new();
+        flatten(self.children.split_off(checkpoint), &mut tokens);
+        self.children.push(SyntaxElement::Node(SyntaxNode::new(
+            SyntaxKind::Error,
+            tokens,
+        )));
+    }
+
+    pub(crate) fn finish(self) -> SyntaxNode {
+        SyntaxNode::new(SyntaxKind::Program, self.children)
+    }
+}
+
+fn flatten(children: Vec<SyntaxElement>, out: &mut Vec<SyntaxElement>) {
+    for child in children {
+        match child {
+            SyntaxElement::Node(node) => flatten(node.children, out),
+            token => out.push(token),
+        }
+    }
+}
+
+// Программа по дереву: каждый дочерний узел Program становится декларацией.
+// Что считать переменной, решает config, как и при разборе, поэтому для дерева,
+// построенного парсером, результат совпадает с программой парсера. Узел, форма
+// которого не соответствует грамматике (например, после правки дерева), становится
+// Declaration::Error.
+pub fn lower(tree: &SyntaxNode, config: &LanguageConfig) -> Program {
+    let mut lowering = Lowering {
+        config,
+        declared: Vec::new(),
+        anonymous: 0,
+    };
+    let mut declarations = Vec::new();
+    for (idx, child) in tree.children.iter().enumerate() {
+        let SyntaxElement::Node(node) = child else {
+            continue;
+        };
+        let declaration = lowering.declaration(node).or_else(|| {
+            // У пустого узла Error позиция — у следующей лексемы
+            let start = node
+                .tokens()
+                .first()
+                .copied()
+                .or_else(|| first_token(&tree.children[idx + 1..]))?;
+            let span = match node.tokens().last() {
+                Some(last) => start.span.to(last.span),
+                None => Span::new(start.span.start, start.span.start),
+            };
+            Some(Declaration::Error { span })
+        });
+        declarations.extend(declaration);
+    }
+
+    Program {
+        declarations,
+        span: tree.span(),
+        lines: LineIndex::new(&tree.text()),
+    }
+}
+
+// Вызов по узлу Call, например цель запроса из parse_goal
+pub(crate) fn lower_call(node: &SyntaxNode, config: &LanguageConfig) -> Option<Call> {
+    Lowering {
+        config,
+        declared: Vec::new(),
+        anonymous: 0,
+    }
+    .call(node)
+}
+
+struct Lowering<'c> {
+    config: &'c LanguageConfig,
+    // Переменные из уже пройденных узлов Vars
+    declared: Vec<String>,
+    // Сколько вхождений '_' уже переименовано
+    anonymous: usize,
+}
+
+impl Lowering<'_> {
+    fn declaration(&mut self, node: &SyntaxNode) -> Option<Declaration> {
+        let span = node.span();
+        match node.kind {
+            // 'declare' K
+            SyntaxKind::Declare => {
+                let fact = self.call(node.nodes().next()?)?;
+                Some(Declaration::Declare { fact, span })
+            }
+            // 'conclusion' K ':' '-' L (',' L)*
+            SyntaxKind::Conclusion => {
+                let mut nodes = node.nodes();
+                let left = self.call(nodes.next()?)?;
+                let right = nodes
+                    .map(|literal| self.literal(literal))
+                    .collect::<Option<Vec<_>>>()?;
+                if right.is_empty() {
+                    return None;
+                }
+                Some(Declaration::Conclusion { left, right, span })
+            }
+            // '?' '-' K | 'query' K
+            SyntaxKind::Query => {
+                let goal = self.call(node.nodes().next()?)?;
+                Some(Declaration::Query { goal, span })
+            }
+            // 'vars' Identifier (',' Identifier)*
+            SyntaxKind::Vars => {
+                let tokens = node.tokens();
+                let names: Vec<String> = tokens
+                    .get(1..)?
+                    .iter()
+                    .filter_map(|token| match &token.kind {
+                        LexemKind::Word(name) => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                self.declared.extend(names.iter().cloned());
+                Some(Declaration::Vars { names, span })
+            }
+            SyntaxKind::Program | SyntaxKind::Literal | SyntaxKind::Call | SyntaxKind::Error => {
+                None
+            }
+        }
+    }
+
+    // L -> 'not' K | K
+    fn literal(&mut self, node: &SyntaxNode) -> Option<Literal> {
+        if node.kind != SyntaxKind::Literal {
+            return None;
+        }
+        let negative = node.children.iter().any(
+            |child| matches!(child, SyntaxElement::Token(token) if token.kind == LexemKind::Not),
+        );
+        Some(Literal {
+            call: self.call(node.nodes().next()?)?,
+            polarity: if negative {
+                Polarity::Negative
+            } else {
+                Polarity::Positive
+            },
+            span: node.span(),
+        })
+    }
+
+    // K -> F A
+    fn call(&mut self, node: &SyntaxNode) -> Option<Call> {
+        if node.kind != SyntaxKind::Call {
+            return None;
+        }
+        let tokens = node.tokens();
+        let name = tokens.first()?;
+        let LexemKind::Word(func) = &name.kind else {
+            return None;
+        };
+        let args = self.arguments(&tokens[1..])?;
+        Some(Call {
+            func: func.clone(),
+            args: args.iter().map(|(value, _)| value.clone()).collect(),
+            span: node.span(),
+            arg_spans: args.iter().map(|(_, t)| t.span).collect(),
+        })
+    }
+
+    // Значения из списка аргументов '(' V (',' V)* ')' вместе с их лексемами;
+    // скобки и запятые пропускаются
+    fn arguments<'t>(&mut self, tokens: &[&'t Lexem]) -> Option<Vec<(Value, &'t Lexem)>> {
+        tokens
+            .iter()
+            .filter(|token| {
+                !matches!(
+                    token.kind,
+                    LexemKind::LParen | LexemKind::RParen | LexemKind::Comma
+                )
+            })
+            .map(|&token| Some((self.value(token)?, token)))
+            .collect()
+    }
+
+    // V -> Variable | Identifier | Integer | String
+    // Каждое вхождение '_' становится новой переменной
+    fn value(&mut self, token: &Lexem) -> Option<Value> {
+        match &token.kind {
+            LexemKind::Word(word)
+                if word == ANONYMOUS_VAR && self.config.is_variable(word, &self.declared) =>
+            {
+                self.anonymous += 1;
+                Some(Value::anonymous(self.anonymous))
+            }
+            LexemKind::Word(word) if self.config.is_variable(word, &self.declared) => {
+                Some(Value::Variable(word.clone()))
+            }
+            LexemKind::Word(word) => Some(Value::Identifier(word.clone())),
+            LexemKind::Integer(n) => Some(Value::Integer(*n)),
+            LexemKind::Str(text) => Some(Value::String(text.clone())),
+            _ => None,
+        }
+    }
+}
+
+// Первая лексема среди элементов дерева
+fn first_token(elements: &[SyntaxElement]) -> Option<&Lexem> {
+    elements.iter().find_map(|element| match element {
+        SyntaxElement::Node(node) => node.tokens().first().copied(),
+        SyntaxElement::Token(token) => Some(token),
+    })
+}
@@ -0,0 +1,271 @@
+use proptest::prelude::*;
+
+use crate::config::{LanguageConfig, Variables};
+use crate::cst::{SyntaxElement, SyntaxKind, SyntaxOutcome, lower};
+use crate::lexer::Lexer;
+use crate::parser::{Declaration, Parser, Value};
+
+fn parse_tree(input: &str) -> SyntaxOutcome {
+    let lexed = Lexer::new().lex_recovering(input);
+    Parser::new(lexed.lexems, LanguageConfig::default())
+        .max_errors(3)
+        .parse_syntax_tree()
+}
+
+#[test]
+fn test_tree_prints_source_back() {
+    let inputs = [
+        "declare Q(Ann);\nconclusion A(x):-Q(x), not B(x)\n",
+        "  \t declare   Q ( Ann ) ;\r\n\r\n  ?- A( x )  \n\n",
+        "",
+        "   \n",
+    ];
+    for input in inputs {
+        assert_eq!(parse_tree(input).tree.text(), input);
+    }
+}
+
+#[test]
+fn test_tree_keeps_broken_declarations() {
+    let input = "declare R(Bob) ;\ndeclare Q(A#n) junk;  conclusion A(x) :- \n";
+    let outcome = parse_tree(input);
+    assert_eq!(outcome.tree.text(), input);
+    assert_eq!(outcome.diagnostics.len(), 2);
+
+    let kinds: Vec<_> = outcome.tree.nodes().map(|node| node.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![SyntaxKind::Error, SyntaxKind::Error, SyntaxKind::Error]
+    );
+}
+
+#[test]
+fn test_tree_stops_at_error_limit_without_losing_text() {
+    let input = "a; b; c; d; e; declare Q(Ann)";
+    let outcome = parse_tree(input);
+    assert_eq!(outcome.diagnostics.len(), 3);
+    assert_eq!(outcome.tree.text(), input);
+}
+
+#[test]
+fn test_tree_structure_matches_program() {
+    let input = "declare Q(Ann); conclusion A(x) :- Q(x), not B(x)";
+    let outcome = parse_tree(input);
+    assert!(outcome.diagnostics.is_empty());
+    assert_eq!(
+        outcome.tree.render_tree(),
+        "Program@0..49\n\
+         \x20 Declare@0..14\n\
+         \x20   \"declare\"@0..7\n\
+         \x20   Call@8..14\n\
+         \x20     \"Q\"@8..9\n\
+         \x20     \"(\"@9..10\n\
+         \x20     \"Ann\"@10..13\n\
+         \x20     \")\"@13..14\n\
+         \x20 \";\"@14..15\n\
+         \x20 Conclusion@16..49\n\
+         \x20   \"conclusion\"@16..26\n\
+         \x20   Call@27..31\n\
+         \x20     \"A\"@27..28\n\
+         \x20     \"(\"@28..29\n\
+         \x20     \"x\"@29..30\n\
+         \x20     \")\"@30..31\n\
+         \x20   \":\"@32..33\n\
+         \x20   \"-\"@33..34\n\
+         \x20   Literal@35..39\n\
+         \x20     Call@35..39\n\
+         \x20       \"Q\"@35..36\n\
+         \x20       \"(\"@36..37\n\
+         \x20       \"x\"@37..38\n\
+         \x20       \")\"@38..39\n\
+         \x20   \",\"@39..40\n\
+         \x20   Literal@41..49\n\
+         \x20     \"not\"@41..44\n\
+         \x20     Call@45..49\n\
+         \x20       \"B\"@45..46\n\
+         \x20       \"(\"@46..47\n\
+         \x20       \"x\"@47..48\n\
+         \x20       \")\"@48..49\n\
+         \x20 \"\"@49..49\n"
+    );
+
+    // Узлы деклараций и декларации программы указывают на одни и те же байты
+    let spans: Vec<_> = outcome.tree.nodes().map(|node| node.span()).collect();
+    let declared: Vec<_> = outcome
+        .program
+        .declarations
+        .iter()
+        .map(Declaration::span)
+        .collect();
+    assert_eq!(spans, declared);
+}
+
+#[test]
+fn test_trailing_trivia_stops_at_line_end() {
+    let outcome = parse_tree("declare Q(Ann) ;  \n  declare B(Bob)");
+    let tokens = outcome.tree.tokens();
+    let semicolon = tokens.iter().find(|t| t.text() == ";").unwrap();
+    assert_eq!(semicolon.trailing[0].text, "  ");
+    let second = tokens
+        .iter()
+        .filter(|t| t.text() == "declare")
+        .nth(1)
+        .unwrap();
+    assert_eq!(second.leading[0].text, "\n  ");
+}
+
+// Краткая запись декларации для сравнения в тестах
+fn describe(declaration: &Declaration) -> String {
+    match declaration {
+        Declaration::Declare { fact, .. } => format!("declare {}", fact),
+        Declaration::Conclusion { left, right, .. } => {
+            let right: Vec<_> = right.iter().map(ToString::to_string).collect();
+            format!("{} :- {}", left, right.join(", "))
+        }
+        Declaration::Query { goal, .. } => format!("?- {}", goal),
+        Declaration::Vars { names, .. } => format!("vars {}", names.join(", ")),
+        Declaration::Error { .. } => "error".to_string(),
+    }
+}
+
+#[test]
+fn test_lower_program() {
+    let input = "declare Q(Ann);\nconclusion A(x):-Q(x), not B(x); ?- A(x); query A(Ann)";
+    let program = parse_tree(input).program;
+    let declarations: Vec<_> = program.declarations.iter().map(describe).collect();
+    assert_eq!(
+        declarations,
+        vec![
+            "declare Q(Ann)",
+            "A(x) :- Q(x), not B(x)",
+            "?- A(x)",
+            "?- A(Ann)",
+        ]
+    );
+    let spans: Vec<_> = program
+        .declarations
+        .iter()
+        .map(|declaration| &input[declaration.span().start..declaration.span().end])
+        .collect();
+    assert_eq!(spans[1], "conclusion A(x):-Q(x), not B(x)");
+    assert_eq!(spans[3], "query A(Ann)");
+    match &program.declarations[1] {
+        Declaration::Conclusion { left, right, .. } => {
+            assert_eq!(&input[left.span.start..left.span.end], "A(x)");
+            assert_eq!(
+                &input[right[1].call.span.start..right[1].call.span.end],
+                "B(x)"
+            );
+        }
+        _ => panic!("expected conclusion declaration"),
+    }
+}
+
+#[test]
+fn test_lower_broken_declarations() {
+    let input = "declare A(Alpha, 3, \"s\") declare B(Bob); Q(x) junk; ; conclusion A(x) :- ";
+    let outcome = parse_tree(input);
+    // Третья ошибка (пустая декларация) исчерпывает предел, conclusion уже не разбирается
+    let declarations: Vec<_> = outcome.program.declarations.iter().map(describe).collect();
+    assert_eq!(
+        declarations,
+        vec![
+            "declare A(Alpha, 3, \"s\")",
+            "declare B(Bob)",
+            "error",
+            "error"
+        ]
+    );
+    // Каждый узел декларации в дереве даёт ровно одну декларацию программы
+    assert_eq!(outcome.tree.nodes().count(), declarations.len());
+}
+
+#[test]
+fn test_lower_uses_declared_variables() {
+    let config = LanguageConfig::new()
+        .any_capitalised()
+        .variables(Variables::Declared)
+        .zero_arity(true);
+    let input = "declare Q(Ann); vars Who; conclusion A(Who) :- Q(Who), not Ready";
+    let lexed = Lexer::new().lex_recovering(input);
+    let outcome = Parser::new(lexed.lexems, config.clone()).parse_syntax_tree();
+    assert!(outcome.diagnostics.is_empty());
+    let program = lower(&outcome.tree, &config);
+    assert_eq!(program, outcome.program);
+    match &program.declarations[2] {
+        Declaration::Conclusion { left, right, .. } => {
+            assert_eq!(left.to_string(), "A(Who)");
+            assert_eq!(right[1].to_string(), "not Ready()");
+        }
+        _ => panic!("expected conclusion declaration"),
+    }
+}
+
+#[test]
+fn test_lower_edited_tree() {
+    // Удаление второй декларации из дерева удаляет её из программы
+    let mut tree = parse_tree("declare Q(Ann); declare B(Bob); declare Q(Cid)").tree;
+    let second = tree
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| matches!(child, SyntaxElement::Node(_)))
+        .map(|(idx, _)| idx)
+        .nth(1)
+        .unwrap();
+    tree.children.drain(second..=second + 1);
+    assert_eq!(tree.text(), "declare Q(Ann); declare Q(Cid)");
+
+    let program = lower(&tree, &LanguageConfig::default());
+    let facts: Vec<_> = program
+        .declarations
+        .iter()
+        .map(|declaration| match declaration {
+            Declaration::Declare { fact, .. } => fact.to_string(),
+            _ => panic!("expected declare declaration"),
+        })
+        .collect();
+    assert_eq!(facts, vec!["Q(Ann)", "Q(Cid)"]);
+}
+
+#[test]
+fn test_lower_renames_each_anonymous_variable() {
+    let config = LanguageConfig::new()
+        .variables(Variables::PrologStyle)
+        .any_capitalised();
+    let input = "conclusion A(X) :- B(X, _), Q(_)";
+    let lexed = Lexer::new().lex_recovering(input);
+    let program = Parser::new(lexed.lexems, config)
+        .parse_syntax_tree()
+        .program;
+    let Declaration::Conclusion { right, .. } = &program.declarations[0] else {
+        panic!("expected conclusion declaration");
+    };
+    let (first, second) = (&right[0].call.args[1], &right[1].call.args[0]);
+    assert!(matches!(first, Value::Variable(_)));
+    assert_ne!(first, second);
+    // В тексте анонимная переменная по-прежнему печатается как '_'
+    assert_eq!(right[0].to_string(), "B(X, _)");
+}
+
+proptest! {
+    // Любой текст из лексем, мусора и пробелов печатается обратно без изменений
+    #[test]
+    fn prop_tree_round_trips(
+        pieces in prop::collection::vec(
+            prop::sample::select(vec![
+                "declare", "conclusion", "query", "not", "vars", "Q", "A", "B", "x", "Ann",
+                "(", ")", ";", ",", ":", "-", "?", " ", "\t", "\n", "\r\n", "#", "ёж",
+                "%", "//", "/*", "*/", "/", "И\u{306}", "Имя",
+                "\"", "'", "\\", "42", "Item_1",
+            ]),
+            0..40,
+        )
+    ) {
+        let input = pieces.concat();
+        let outcome = parse_tree(&input);
+        prop_assert_eq!(&outcome.tree.text(), &input);
+        // Каждый узел декларации в дереве даёт ровно одну декларацию программы
+        prop_assert_eq!(outcome.tree.nodes().count(), outcome.program.declarations.len());
+    }
+}
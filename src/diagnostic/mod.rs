@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests;
+
+// Стабильные коды диагностик. Коды не переиспользуются: инструменты могут
+// опираться на них вместо текста сообщения.
+pub mod codes {
+    // Лексический анализ
+    pub const UNEXPECTED_CHARACTER: &str = "E0001";
+    pub const UNTERMINATED_COMMENT: &str = "E0002";
+    pub const UNTERMINATED_STRING: &str = "E0003";
+    pub const INVALID_ESCAPE: &str = "E0004";
+    pub const INTEGER_OVERFLOW: &str = "E0005";
+
+    // Синтаксический анализ
+    pub const EXPECTED_TOKEN: &str = "E0101";
+    pub const UNKNOWN_FUNCTION: &str = "E0102";
+    pub const CONFIGURED_ARITY: &str = "E0103";
+    pub const TRAILING_TOKENS: &str = "E0104";
+    pub const VARS_NOT_ALLOWED: &str = "E0105";
+    pub const NON_GROUND_FACT: &str = "E0106";
+
+    // Семантический анализ
+    pub const ARITY_MISMATCH: &str = "E0201";
+    pub const UNBOUND_VARIABLE: &str = "E0202";
+    pub const NOT_STRATIFIABLE: &str = "E0203";
+
+    // Вычисление и запросы
+    pub const EVALUATION_MISMATCH: &str = "E0301";
+    pub const DEPTH_LIMIT_EXCEEDED: &str = "E0302";
+    pub const NON_GROUND_NEGATION: &str = "E0303";
+
+    // Предупреждения
+    pub const MIXED_SCRIPT: &str = "W0001";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+// Фрагмент исходного текста в том виде, в каком он показывается пользователю:
+// начало (строка и столбец с 1) и длина в символах. Смещения в байтах — span::Span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+impl Location {
+    pub fn new(line: usize, column: usize, len: usize) -> Self {
+        Self { line, column, len }
+    }
+}
+
+// Дополнительная подпись к другому месту программы
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub location: Location,
+    pub message: String,
+}
+
+// Сообщение о проблеме в программе. Лексер, парсер и семантические проверки
+// сообщают об ошибках одним и тем же типом.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub location: Location,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        code: &'static str,
+        message: impl Into<String>,
+        location: Location,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            location,
+            labels: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn error(code: &'static str, message: impl Into<String>, location: Location) -> Self {
+        Self::new(Severity::Error, code, message, location)
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>, location: Location) -> Self {
+        Self::new(Severity::Warning, code, message, location)
+    }
+
+    pub fn with_label(mut self, location: Location, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            location,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+// Заголовок диагностики: error[E0102]: Expected function name: Q, B or A at 1:9
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {} at {}:{}",
+            self.severity, self.code, self.message, self.location.line, self.location.column
+        )
+    }
+}
+
+impl std::error::Error for Diagnostic {}
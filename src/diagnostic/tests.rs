@@ -0,0 +1,29 @@
+use crate::diagnostic::{Diagnostic, Location, Severity, codes};
+
+#[test]
+fn test_diagnostic_builder() {
+    let diagnostic = Diagnostic::error(
+        codes::ARITY_MISMATCH,
+        "Arity mismatch",
+        Location::new(2, 12, 1),
+    )
+    .with_label(Location::new(1, 1, 7), "first use")
+    .with_help("use the same number of arguments");
+    assert!(diagnostic.is_error());
+    assert_eq!(diagnostic.labels.len(), 1);
+    assert_eq!(diagnostic.labels[0].location.column, 1);
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("use the same number of arguments")
+    );
+}
+
+#[test]
+fn test_diagnostic_display() {
+    let diagnostic = Diagnostic::warning("W0001", "Something odd", Location::new(3, 4, 2));
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(
+        diagnostic.to_string(),
+        "warning[W0001]: Something odd at 3:4"
+    );
+}
@@ -0,0 +1,614 @@
+#[cfg(test)]
+mod tests;
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::analysis::{Semantics, check_safety};
+use crate::diagnostic::{Diagnostic, Location, codes};
+use crate::parser::{Call, Declaration, Literal, Program, Value};
+use crate::proof::{Failure, Proof, ProofStep, RuleAttempt, WhyNot};
+use crate::span::Span;
+use crate::stratify::DependencyGraph;
+use crate::unify::{Substitution, unify};
+
+// Кортеж констант одного факта, например (Alpha, Beta) для A(Alpha, Beta)
+pub type Tuple = Vec<Value>;
+
+// Как факт попал в модель: объявлен через 'declare' или выведен правилом
+#[derive(Debug, Clone)]
+enum Derivation {
+    Declared { span: Span },
+    Rule { rule: usize, bindings: Substitution },
+}
+
+// Истинностное значение факта в модели
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truth {
+    True,
+    False,
+    // Бывает только в обоснованной семантике: факт зависит от себя через отрицание
+    Undefined,
+}
+
+impl std::fmt::Display for Truth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Truth::True => write!(f, "true"),
+            Truth::False => write!(f, "false"),
+            Truth::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+// Множество всех выведенных фактов, сгруппированных по предикатам.
+// Для каждого факта хранится первый найденный вывод: посылки этого вывода
+// были получены раньше самого факта, поэтому дерево доказательства конечно.
+// undefined — факты, не определённые в обоснованной модели; они не входят в relations.
+#[derive(Debug, Clone, Default)]
+pub struct Model {
+    relations: BTreeMap<String, BTreeMap<Tuple, Derivation>>,
+    undefined: BTreeMap<String, BTreeSet<Tuple>>,
+    rules: Vec<(Call, Vec<Literal>)>,
+}
+
+// Модели равны, если совпадают их факты; выводы фактов не сравниваются
+impl PartialEq for Model {
+    fn eq(&self, other: &Self) -> bool {
+        self.relations.len() == other.relations.len()
+            && self.relations.iter().all(|(func, tuples)| {
+                other
+                    .relations
+                    .get(func)
+                    .is_some_and(|others| tuples.keys().eq(others.keys()))
+            })
+            && self.undefined == other.undefined
+    }
+}
+
+impl Eq for Model {}
+
+impl Model {
+    // Проверка, выведен ли данный ground-вызов
+    pub fn contains(&self, call: &Call) -> bool {
+        self.contains_tuple(&call.func, &call.args)
+    }
+
+    // Все факты модели в детерминированном порядке (по предикату, затем по аргументам)
+    pub fn facts(&self) -> Vec<Call> {
+        self.relations
+            .iter()
+            .flat_map(|(func, tuples)| {
+                tuples
+                    .keys()
+                    .map(move |tuple| Call::new(func.clone(), tuple.clone()))
+            })
+            .collect()
+    }
+
+    // Значение ground-вызова: выведен, не определён или ложен
+    pub fn truth(&self, call: &Call) -> Truth {
+        if self.contains(call) {
+            Truth::True
+        } else if self
+            .undefined
+            .get(&call.func)
+            .is_some_and(|tuples| tuples.contains(&call.args))
+        {
+            Truth::Undefined
+        } else {
+            Truth::False
+        }
+    }
+
+    // Неопределённые факты обоснованной модели в том же порядке, что и facts()
+    pub fn undefined(&self) -> Vec<Call> {
+        self.undefined
+            .iter()
+            .flat_map(|(func, tuples)| {
+                tuples
+                    .iter()
+                    .map(move |tuple| Call::new(func.clone(), tuple.clone()))
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.relations.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Факты этой модели, отсутствующие в другой
+    pub fn difference(&self, other: &Model) -> Vec<Call> {
+        self.facts()
+            .into_iter()
+            .filter(|fact| !other.contains(fact))
+            .collect()
+    }
+
+    // Дерево доказательства факта; None, если факт не выведен
+    pub fn proof(&self, call: &Call) -> Option<Proof> {
+        let derivation = self.relations.get(&call.func)?.get(&call.args)?;
+        let fact = Call::new(call.func.clone(), call.args.clone());
+        let step = match derivation {
+            Derivation::Declared { span } => ProofStep::Declared { span: *span },
+            Derivation::Rule { rule, bindings } => {
+                let (head, body) = &self.rules[*rule];
+                let premises = body
+                    .iter()
+                    .map(|literal| {
+                        let premise = bindings.apply(&literal.call);
+                        if literal.is_negative() {
+                            Some(Proof {
+                                fact: premise,
+                                step: ProofStep::Absent,
+                            })
+                        } else {
+                            self.proof(&premise)
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                ProofStep::Rule {
+                    head: head.clone(),
+                    body: body.clone(),
+                    bindings: bindings.clone(),
+                    premises,
+                }
+            }
+        };
+        Some(Proof { fact, step })
+    }
+
+    // Объяснение, почему ground-факт не выведен: для каждого правила, заключение которого
+    // унифицируется с фактом, перечисляются привязки, при которых тело не выполнилось,
+    // и первый невыполненный атом. None, если факт выведен.
+    pub fn why_not(&self, call: &Call) -> Option<WhyNot> {
+        if self.contains(call) {
+            return None;
+        }
+
+        let attempts = self
+            .rules
+            .iter()
+            .filter_map(|(head, body)| {
+                let bindings = unify(head, call)?;
+                // Порядок проверки тот же, что при вычислении: сначала положительные литералы
+                let (negative, positive): (Vec<_>, Vec<_>) =
+                    (0..body.len()).partition(|&idx| body[idx].is_negative());
+                let order: Vec<_> = positive.into_iter().chain(negative).collect();
+                let mut failures = Vec::new();
+                self.find_failures(body, &order, bindings, &mut failures);
+                Some(RuleAttempt {
+                    head: head.clone(),
+                    body: body.clone(),
+                    failures,
+                })
+            })
+            .collect();
+
+        Some(WhyNot {
+            fact: Call::new(call.func.clone(), call.args.clone()),
+            attempts,
+        })
+    }
+
+    // Обход литералов тела в порядке order; ветка, на которой положительный атом
+    // не сопоставился ни с одним фактом или отрицаемый факт выведен, даёт одну запись о неудаче
+    fn find_failures(
+        &self,
+        body: &[Literal],
+        order: &[usize],
+        bindings: Substitution,
+        failures: &mut Vec<Failure>,
+    ) {
+        let Some((&idx, rest)) = order.split_first() else {
+            return;
+        };
+        let literal = &body[idx];
+
+        if literal.is_negative() {
+            let fact = bindings.apply(&literal.call);
+            if self.contains(&fact) {
+                failures.push(Failure {
+                    atom: idx,
+                    call: fact,
+                    bindings,
+                });
+            } else {
+                self.find_failures(body, rest, bindings, failures);
+            }
+            return;
+        }
+
+        let mut matched = false;
+        for tuple in self.tuples(&literal.call.func) {
+            if let Some(extended) = match_tuple(&literal.call, tuple, &bindings) {
+                matched = true;
+                self.find_failures(body, rest, extended, failures);
+            }
+        }
+
+        if !matched {
+            failures.push(Failure {
+                atom: idx,
+                call: bindings.apply(&literal.call),
+                bindings,
+            });
+        }
+    }
+
+    fn contains_tuple(&self, func: &str, tuple: &Tuple) -> bool {
+        self.relations
+            .get(func)
+            .is_some_and(|tuples| tuples.contains_key(tuple))
+    }
+
+    fn extend(&mut self, other: Model) {
+        for (func, tuples) in other.relations {
+            let entry = self.relations.entry(func).or_default();
+            for (tuple, derivation) in tuples {
+                entry.entry(tuple).or_insert(derivation);
+            }
+        }
+    }
+
+    fn tuples(&self, func: &str) -> impl Iterator<Item = &Tuple> {
+        self.relations
+            .get(func)
+            .into_iter()
+            .flat_map(BTreeMap::keys)
+    }
+
+    // Возвращает true, если факт новый; у уже известного факта вывод не меняется
+    fn insert(&mut self, func: &str, tuple: Tuple, derivation: Derivation) -> bool {
+        let tuples = self.relations.entry(func.to_string()).or_default();
+        if tuples.contains_key(&tuple) {
+            return false;
+        }
+        tuples.insert(tuple, derivation);
+        true
+    }
+}
+
+// Правило вида left :- right; idx — номер правила в порядке объявления.
+// Положительные литералы соединяются с фактами, отрицательные лишь проверяются
+// после того, как все переменные связаны.
+struct Rule<'a> {
+    idx: usize,
+    head: &'a Call,
+    body: &'a [Literal],
+    positive: Vec<&'a Call>,
+    negative: Vec<&'a Call>,
+}
+
+impl<'a> Rule<'a> {
+    fn new(idx: usize, head: &'a Call, body: &'a [Literal]) -> Self {
+        let (negative, positive): (Vec<_>, Vec<_>) =
+            body.iter().partition(|literal| literal.is_negative());
+        Self {
+            idx,
+            head,
+            body,
+            positive: positive.into_iter().map(|literal| &literal.call).collect(),
+            negative: negative.into_iter().map(|literal| &literal.call).collect(),
+        }
+    }
+}
+
+// Стратегия вычисления неподвижной точки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    // На каждом шаге тела правил соединяются со всеми известными фактами
+    Naive,
+    // На каждом шаге хотя бы один атом тела соединяется только с фактами,
+    // появившимися на предыдущем шаге (дельтой)
+    #[default]
+    SemiNaive,
+}
+
+pub struct Evaluator<'a> {
+    program: &'a Program,
+    strategy: Strategy,
+    semantics: Semantics,
+    cross_check: bool,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            strategy: Strategy::default(),
+            semantics: Semantics::default(),
+            cross_check: false,
+        }
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn semantics(mut self, semantics: Semantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
+
+    // Дополнительно вычислить модель второй стратегией и сравнить результаты
+    pub fn cross_check(mut self, enabled: bool) -> Self {
+        self.cross_check = enabled;
+        self
+    }
+
+    // Вычисление снизу вверх: 'declare' — исходные факты, 'conclusion' — правила Хорна.
+    // Правила применяются, пока появляются новые факты (наименьшая неподвижная точка).
+    pub fn evaluate(&self) -> Result<Model, Diagnostic> {
+        // Ограниченность переменных проверяет семантический анализ; вычислитель
+        // сообщает о первом нарушении, если анализ не запускался
+        if let Some(error) = check_safety(self.program).into_iter().next() {
+            return Err(error);
+        }
+
+        let mut facts = Model::default();
+        let mut rules = Vec::new();
+
+        for declaration in &self.program.declarations {
+            match declaration {
+                Declaration::Declare { fact, span } => {
+                    let derivation = Derivation::Declared { span: *span };
+                    facts.insert(&fact.func, fact.args.clone(), derivation);
+                }
+                Declaration::Conclusion { left, right, .. } => {
+                    rules.push(Rule::new(rules.len(), left, right));
+                }
+                Declaration::Query { .. }
+                | Declaration::Vars { .. }
+                | Declaration::Error { .. } => {}
+            }
+        }
+
+        let mut model = self.compute(self.strategy, facts.clone(), &rules)?;
+
+        if self.cross_check {
+            let other = match self.strategy {
+                Strategy::Naive => Strategy::SemiNaive,
+                Strategy::SemiNaive => Strategy::Naive,
+            };
+            let expected = self.compute(other, facts, &rules)?;
+            if expected != model {
+                return Err(Diagnostic::error(
+                    codes::EVALUATION_MISMATCH,
+                    format!(
+                        "{:?} and {:?} evaluation disagree: missing [{}], extra [{}], \
+                         undefined [{}] vs [{}]",
+                        self.strategy,
+                        other,
+                        list_facts(expected.difference(&model)),
+                        list_facts(model.difference(&expected)),
+                        list_facts(model.undefined()),
+                        list_facts(expected.undefined()),
+                    ),
+                    Location::default(),
+                ));
+            }
+        }
+
+        model.rules = rules
+            .iter()
+            .map(|rule| (rule.head.clone(), rule.body.to_vec()))
+            .collect();
+        Ok(model)
+    }
+}
+
+impl Evaluator<'_> {
+    fn compute(
+        &self,
+        strategy: Strategy,
+        facts: Model,
+        rules: &[Rule],
+    ) -> Result<Model, Diagnostic> {
+        match self.semantics {
+            Semantics::Stratified => {
+                // Страты вычисляются по порядку: к началу вычисления страта все предикаты
+                // под отрицанием уже полностью вычислены в нижних стратах
+                let strata = DependencyGraph::new(self.program).stratify()?;
+
+                let mut model = facts;
+                for predicates in &strata {
+                    let rules: Vec<&Rule> = rules
+                        .iter()
+                        .filter(|rule| predicates.contains(&rule.head.func))
+                        .collect();
+                    let negated = model.clone();
+                    model = fixpoint(strategy, model, &rules, &negated);
+                }
+                Ok(model)
+            }
+            Semantics::WellFounded => Ok(alternating_fixpoint(strategy, facts, rules)),
+        }
+    }
+}
+
+// Чередующаяся неподвижная точка (Van Gelder). Γ(I) — наименьшая модель, в которой
+// 'not K' выполнено, если K нет в I. Γ антимонотонна, поэтому Γ(∅) — оценка сверху
+// возможных фактов, Γ(Γ(∅)) — оценка снизу истинных; чередование сужает обе оценки.
+// Когда нижняя оценка перестаёт расти, она даёт истинные факты, верхняя — все
+// неложные, их разность — неопределённые.
+fn alternating_fixpoint(strategy: Strategy, facts: Model, rules: &[Rule]) -> Model {
+    let rules: Vec<&Rule> = rules.iter().collect();
+    let gamma = |assumed: &Model| fixpoint(strategy, facts.clone(), &rules, assumed);
+
+    let mut possible = gamma(&Model::default());
+    let mut certain = gamma(&possible);
+    loop {
+        possible = gamma(&certain);
+        let next = gamma(&possible);
+        if next == certain {
+            break;
+        }
+        certain = next;
+    }
+
+    for fact in possible.difference(&certain) {
+        certain
+            .undefined
+            .entry(fact.func)
+            .or_default()
+            .insert(fact.args);
+    }
+    certain
+}
+
+// Наименьшая неподвижная точка правил; отрицаемые факты ищутся в negated
+fn fixpoint(strategy: Strategy, model: Model, rules: &[&Rule], negated: &Model) -> Model {
+    match strategy {
+        Strategy::Naive => naive(model, rules, negated),
+        Strategy::SemiNaive => semi_naive(model, rules, negated),
+    }
+}
+
+fn naive(mut model: Model, rules: &[&Rule], negated: &Model) -> Model {
+    loop {
+        let mut derived = Vec::new();
+        for rule in rules {
+            for bindings in join(rule, |_| vec![&model], negated) {
+                derived.push((rule, bindings));
+            }
+        }
+
+        let mut changed = false;
+        for (rule, bindings) in derived {
+            let tuple = bindings.apply(rule.head).args;
+            let derivation = Derivation::Rule {
+                rule: rule.idx,
+                bindings,
+            };
+            changed |= model.insert(&rule.head.func, tuple, derivation);
+        }
+
+        if !changed {
+            return model;
+        }
+    }
+}
+
+// Полунаивное вычисление: факты делятся на старые (old) и новые за прошлый шаг (delta).
+// Для каждой позиции i положительный атом i берётся из delta, атомы левее — только из old,
+// атомы правее — из old и delta. Так каждое соединение рассматривается ровно один раз.
+// Правила без положительных атомов не зависят от дельты: они срабатывают один раз
+// до начала цикла, и их факты входят в первую дельту.
+fn semi_naive(facts: Model, rules: &[&Rule], negated: &Model) -> Model {
+    let mut old = Model::default();
+    let mut delta = facts;
+
+    for rule in rules.iter().filter(|rule| rule.positive.is_empty()) {
+        for bindings in join(rule, |_| Vec::new(), negated) {
+            let tuple = bindings.apply(rule.head).args;
+            let derivation = Derivation::Rule {
+                rule: rule.idx,
+                bindings,
+            };
+            delta.insert(&rule.head.func, tuple, derivation);
+        }
+    }
+
+    while !delta.is_empty() {
+        let mut next = Model::default();
+        for rule in rules {
+            for i in 0..rule.positive.len() {
+                if delta.tuples(&rule.positive[i].func).next().is_none() {
+                    continue;
+                }
+
+                let sources = |j: usize| match j.cmp(&i) {
+                    Ordering::Less => vec![&old],
+                    Ordering::Equal => vec![&delta],
+                    Ordering::Greater => vec![&old, &delta],
+                };
+                for bindings in join(rule, sources, negated) {
+                    let tuple = bindings.apply(rule.head).args;
+                    if !old.contains_tuple(&rule.head.func, &tuple)
+                        && !delta.contains_tuple(&rule.head.func, &tuple)
+                    {
+                        let derivation = Derivation::Rule {
+                            rule: rule.idx,
+                            bindings,
+                        };
+                        next.insert(&rule.head.func, tuple, derivation);
+                    }
+                }
+            }
+        }
+
+        old.extend(delta);
+        delta = next;
+    }
+
+    old
+}
+
+fn list_facts(facts: Vec<Call>) -> String {
+    facts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Соединение положительных литералов правила с фактами слева направо;
+// sources(j) задаёт, из каких наборов фактов берутся кортежи для j-го атома.
+// Затем отбрасываются привязки, при которых отрицаемый факт есть в negated.
+fn join<'m>(
+    rule: &Rule,
+    sources: impl Fn(usize) -> Vec<&'m Model>,
+    negated: &Model,
+) -> Vec<Substitution> {
+    let mut current = vec![Substitution::new()];
+    for (j, call) in rule.positive.iter().enumerate() {
+        let models = sources(j);
+        let mut next = Vec::new();
+        for bindings in &current {
+            for tuple in models.iter().flat_map(|model| model.tuples(&call.func)) {
+                if let Some(extended) = match_tuple(call, tuple, bindings) {
+                    next.push(extended);
+                }
+            }
+        }
+        current = next;
+    }
+
+    current.retain(|bindings| {
+        rule.negative.iter().all(|call| {
+            let fact = bindings.apply(call);
+            !negated.contains(&fact)
+        })
+    });
+    current
+}
+
+// Сопоставление аргументов вызова с кортежем факта при уже имеющихся привязках
+fn match_tuple(call: &Call, tuple: &Tuple, bindings: &Substitution) -> Option<Substitution> {
+    if call.args.len() != tuple.len() {
+        return None;
+    }
+
+    let mut extended = bindings.clone();
+    for (arg, value) in call.args.iter().zip(tuple) {
+        match arg {
+            Value::Variable(var) => match extended.get(var) {
+                Some(bound) if bound != value => return None,
+                Some(_) => {}
+                None => extended.bind(var.clone(), value.clone()),
+            },
+            constant => {
+                if constant != value {
+                    return None;
+                }
+            }
+        }
+    }
+    Some(extended)
+}
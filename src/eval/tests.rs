@@ -0,0 +1,278 @@
+use crate::analysis::Semantics;
+use crate::config::LanguageConfig;
+use crate::diagnostic::codes;
+use crate::eval::{Evaluator, Strategy, Truth};
+use crate::lexer::Lexer;
+use crate::parser::{Parser, Program};
+
+fn parse(input: &str) -> Program {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    parser.parse_program().expect("parsing failed")
+}
+
+fn facts(input: &str) -> Vec<String> {
+    let program = parse(input);
+    let model = Evaluator::new(&program)
+        .evaluate()
+        .expect("evaluation failed");
+    model.facts().iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_eval_declarations_are_facts() {
+    let facts = facts("declare Q(Alpha); declare B(Beta)");
+    assert_eq!(facts, vec!["B(Beta)", "Q(Alpha)"]);
+}
+
+#[test]
+fn test_eval_single_rule() {
+    let facts = facts("declare Q(Alpha); conclusion A(x):-Q(x)");
+    assert_eq!(facts, vec!["A(Alpha)", "Q(Alpha)"]);
+}
+
+#[test]
+fn test_eval_join_on_shared_variable() {
+    let facts =
+        facts("declare Q(Alpha); declare Q(Beta); declare B(Beta); conclusion A(x):-Q(x),B(x)");
+    assert!(facts.contains(&"A(Beta)".to_string()));
+    assert!(!facts.contains(&"A(Alpha)".to_string()));
+}
+
+#[test]
+fn test_eval_cartesian_product_and_constants() {
+    let facts = facts(
+        "declare Q(Alpha); declare B(Beta); declare B(Gamma); conclusion A(x,y,Id):-Q(x),B(y)",
+    );
+    assert!(facts.contains(&"A(Alpha, Beta, Id)".to_string()));
+    assert!(facts.contains(&"A(Alpha, Gamma, Id)".to_string()));
+}
+
+#[test]
+fn test_eval_rules_chain_until_fixpoint() {
+    let facts = facts("declare Q(Alpha); conclusion A(x):-B(x); conclusion B(x):-Q(x)");
+    assert_eq!(facts, vec!["A(Alpha)", "B(Alpha)", "Q(Alpha)"]);
+}
+
+#[test]
+fn test_eval_arity_mismatch_does_not_match() {
+    let facts = facts("declare Q(Alpha); conclusion A(x,y):-Q(x),Q(y),B(x,y)");
+    assert_eq!(facts, vec!["Q(Alpha)"]);
+}
+
+#[test]
+fn test_eval_error_unbound_head_variable() {
+    let program = parse("declare Q(Alpha); conclusion A(x,y):-Q(x)");
+    let error = Evaluator::new(&program)
+        .evaluate()
+        .expect_err("expected evaluation error");
+    // Та же диагностика, что и у семантического анализа
+    assert_eq!(error.code, codes::UNBOUND_VARIABLE);
+    assert_eq!((error.location.line, error.location.column), (1, 34));
+    assert_eq!(
+        error.message,
+        "Variable 'y' in conclusion A(x, y) is not bound by a positive literal in its body"
+    );
+}
+
+const TRANSITIVE: &str = "declare Q(Ann); declare Q(Bob); declare Q(Cid); declare Q(Dan); \
+    declare B(Bob); declare B(Cid); declare B(Dan); declare B(Eve); \
+    conclusion A(x,y):-Q(x),B(y),Q(y); \
+    conclusion A(x,z):-A(x,y),A(y,z)";
+
+#[test]
+fn test_eval_semi_naive_matches_naive() {
+    let program = parse(TRANSITIVE);
+    let naive = Evaluator::new(&program)
+        .strategy(Strategy::Naive)
+        .evaluate()
+        .expect("naive evaluation failed");
+    let semi_naive = Evaluator::new(&program)
+        .strategy(Strategy::SemiNaive)
+        .evaluate()
+        .expect("semi-naive evaluation failed");
+    assert_eq!(naive, semi_naive);
+    assert_eq!(naive.facts().iter().filter(|f| f.func == "A").count(), 12);
+}
+
+#[test]
+fn test_eval_cross_check_passes() {
+    let program = parse(TRANSITIVE);
+    let model = Evaluator::new(&program)
+        .cross_check(true)
+        .evaluate()
+        .expect("cross-checked evaluation failed");
+    assert!(model.facts().iter().any(|f| f.to_string() == "A(Ann, Dan)"));
+}
+
+#[test]
+fn test_eval_negated_literal() {
+    let facts = facts(
+        "declare Q(Ann); declare Q(Bob); declare B(Bob); \
+         conclusion A(x):-Q(x),not B(x)",
+    );
+    assert!(facts.contains(&"A(Ann)".to_string()));
+    assert!(!facts.contains(&"A(Bob)".to_string()));
+}
+
+#[test]
+fn test_eval_rule_with_only_negated_literals() {
+    // У правила нет положительных атомов, поэтому полунаивное вычисление
+    // не может запустить его по дельте
+    let program = parse("declare Q(Alpha); conclusion A(Alpha):-not B(Alpha)");
+    for semantics in [Semantics::Stratified, Semantics::WellFounded] {
+        let model = Evaluator::new(&program)
+            .semantics(semantics)
+            .cross_check(true)
+            .evaluate()
+            .expect("cross-checked evaluation failed");
+        let facts: Vec<String> = model.facts().iter().map(ToString::to_string).collect();
+        assert_eq!(facts, vec!["A(Alpha)", "Q(Alpha)"]);
+    }
+}
+
+#[test]
+fn test_eval_negation_waits_for_lower_stratum() {
+    // B выводится правилом, поэтому A должен вычисляться только после B
+    let program = parse(
+        "declare Q(Ann); declare Q(Bob); conclusion B(x):-Q(x),Q(Bob); \
+         conclusion A(x):-Q(x),not B(x)",
+    );
+    for strategy in [Strategy::Naive, Strategy::SemiNaive] {
+        let model = Evaluator::new(&program)
+            .strategy(strategy)
+            .evaluate()
+            .expect("evaluation failed");
+        assert!(model.facts().iter().all(|f| f.func != "A"));
+    }
+}
+
+#[test]
+fn test_eval_error_negated_variable_not_bound() {
+    let program = parse("declare Q(Ann); conclusion A(x):-Q(x),not B(y)");
+    let error = Evaluator::new(&program)
+        .evaluate()
+        .expect_err("expected evaluation error");
+    assert!(error.message.contains("Variable 'y'"));
+}
+
+#[test]
+fn test_eval_error_negation_in_cycle() {
+    let program = parse("declare Q(Ann); conclusion A(x):-Q(x),not B(x); conclusion B(x):-A(x)");
+    let error = Evaluator::new(&program)
+        .evaluate()
+        .expect_err("expected evaluation error");
+    assert_eq!(error.code, codes::NOT_STRATIFIABLE);
+    assert!(error.message.contains("not stratifiable"));
+}
+
+fn well_founded(input: &str) -> crate::eval::Model {
+    Evaluator::new(&parse(input))
+        .semantics(Semantics::WellFounded)
+        .cross_check(true)
+        .evaluate()
+        .expect("well-founded evaluation failed")
+}
+
+fn call(input: &str) -> crate::parser::Call {
+    let tokens = Lexer::new().lex(input).expect("lexing failed");
+    Parser::new(tokens, LanguageConfig::default())
+        .parse_goal()
+        .expect("parsing fact failed")
+}
+
+#[test]
+fn test_well_founded_matches_stratified_model() {
+    let input = "declare Q(Ann); declare Q(Bob); declare B(Bob); \
+                 conclusion A(x):-Q(x),not B(x)";
+    let model = well_founded(input);
+    let stratified = Evaluator::new(&parse(input))
+        .evaluate()
+        .expect("evaluation failed");
+    assert_eq!(model, stratified);
+    assert!(model.undefined().is_empty());
+}
+
+#[test]
+fn test_well_founded_mutual_negation_is_undefined() {
+    let model = well_founded(
+        "declare Q(Ann); declare Q(Bob); declare B(Bob); \
+         conclusion A(x):-Q(x),not B(x); conclusion B(x):-Q(x),not A(x)",
+    );
+    assert_eq!(model.truth(&call("B(Bob)")), Truth::True);
+    assert_eq!(model.truth(&call("A(Bob)")), Truth::False);
+    assert_eq!(model.truth(&call("A(Ann)")), Truth::Undefined);
+    let undefined: Vec<_> = model.undefined().iter().map(ToString::to_string).collect();
+    assert_eq!(undefined, vec!["A(Ann)", "B(Ann)"]);
+}
+
+#[test]
+fn test_well_founded_self_negation_blocks_dependants() {
+    // A(x) зависит от себя через отрицание и не определён; B зависит от A положительно
+    // и тоже не определён, поэтому истинными остаются только объявленные факты
+    let model = well_founded(
+        "declare Q(Ann); declare Q(Bob); \
+         conclusion A(x):-Q(x),not A(x),not B(Bob); conclusion B(x):-A(x)",
+    );
+    assert!(model.facts().iter().all(|f| f.func == "Q"));
+    let undefined: Vec<_> = model.undefined().iter().map(ToString::to_string).collect();
+    assert_eq!(undefined, vec!["A(Ann)", "A(Bob)", "B(Ann)", "B(Bob)"]);
+}
+
+#[test]
+fn test_well_founded_resolves_negation_through_false_facts() {
+    // B никогда не выводится, поэтому цикл через отрицание разрешается: A истинен, B ложен
+    let model =
+        well_founded("declare Q(Ann); conclusion A(x):-Q(x),not B(x); conclusion B(x):-A(x),B(x)");
+    assert_eq!(model.truth(&call("A(Ann)")), Truth::True);
+    assert_eq!(model.truth(&call("B(Ann)")), Truth::False);
+    assert!(model.undefined().is_empty());
+}
+
+#[test]
+fn test_eval_integer_and_string_constants() {
+    let facts = facts(
+        "declare B(\"New York\"); declare Q(42); declare Q(\"42\"); conclusion A(x):-Q(x), B(\"New York\")",
+    );
+    assert_eq!(
+        facts,
+        vec![
+            "A(42)",
+            "A(\"42\")",
+            "B(\"New York\")",
+            "Q(42)",
+            "Q(\"42\")"
+        ]
+    );
+}
+
+#[test]
+fn test_eval_multi_argument_facts() {
+    let facts =
+        facts("declare A(Ann, Bob); declare A(Bob, Cid); conclusion B(x, z):-A(x, y), A(y, z)");
+    assert_eq!(facts, vec!["A(Ann, Bob)", "A(Bob, Cid)", "B(Ann, Cid)"]);
+}
+
+#[test]
+fn test_eval_zero_arity_atoms() {
+    let config = LanguageConfig::new().any_capitalised().zero_arity(true);
+    let tokens = Lexer::new()
+        .lex(
+            "declare Ready; declare Q(Alpha); conclusion Go(x):-Q(x), Ready(); \
+             conclusion Done:-Go(x), not Stop; conclusion Idle():-not Busy()",
+        )
+        .expect("lexing failed");
+    let program = Parser::new(tokens, config)
+        .parse_program()
+        .expect("parsing failed");
+    let model = Evaluator::new(&program)
+        .cross_check(true)
+        .evaluate()
+        .expect("cross-checked evaluation failed");
+    let facts: Vec<String> = model.facts().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        facts,
+        vec!["Done()", "Go(Alpha)", "Idle()", "Q(Alpha)", "Ready()"]
+    );
+}
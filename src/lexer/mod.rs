@@ -1,157 +1,530 @@
 #[cfg(test)]
 mod tests;
 
+use crate::diagnostic::{Diagnostic, Location, codes};
+use crate::span::{LineIndex, Span};
+use unicode_normalization::UnicodeNormalization;
+use unicode_script::{Script, UnicodeScript};
+use unicode_xid::UnicodeXID;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LexemKind {
     Word(String),
+    // Целое число без знака
+    Integer(i64),
+    // Строка в одинарных или двойных кавычках; escape-последовательности уже разобраны
+    Str(String),
     LParen,
     RParen,
     Semicolon,
     Comma,
     Colon,
     Minus,
+    Question,
     Declare,
     Conclusion,
+    Query,
+    Not,
+    Vars,
+    // Последовательность недопустимых символов; ошибка о ней уже записана лексером
+    Error(String),
     Eof,
 }
 
+// span — байты лексемы в исходном тексте; строку и столбец для сообщений даёт LineIndex.
+// Слово в Word приведено к NFC, text — лексема в точности как в исходном тексте.
+// leading и trailing — пробелы вокруг лексемы: к лексеме относятся пробелы до конца
+// её строки, всё остальное — к следующей лексеме (остаток файла — к концу файла).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Lexem {
     pub kind: LexemKind,
-    pub line: usize,
-    pub column: usize,
+    pub span: Span,
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
+    text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    // '% ...' или '// ...' до конца строки (без перевода строки)
+    LineComment,
+    // '/* ... */', вложенные комментарии допускаются
+    BlockComment,
 }
 
+// Текст между лексемами, не влияющий на разбор: пробелы и комментарии
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct LexError {
-    pub message: String,
-    pub line: usize,
-    pub column: usize,
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub span: Span,
 }
 
-impl std::fmt::Display for LexError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} at {}:{}", self.message, self.line, self.column)
+impl Lexem {
+    fn new(kind: LexemKind, text: String, span: Span) -> Self {
+        Self {
+            kind,
+            text,
+            span,
+            leading: Vec::new(),
+            trailing: Vec::new(),
+        }
+    }
+
+    // Текст лексемы в исходном тексте (без пробелов вокруг)
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // Ошибка, которую описывает лексема Error; у остальных лексем её нет
+    pub fn diagnostic(&self, lines: &LineIndex) -> Option<Diagnostic> {
+        let LexemKind::Error(run) = &self.kind else {
+            return None;
+        };
+        let message = if run.chars().count() > 1 {
+            format!("Unexpected characters '{}'", run)
+        } else {
+            format!("Unexpected character '{}'", run)
+        };
+        Some(Diagnostic::error(
+            codes::UNEXPECTED_CHARACTER,
+            message,
+            lines.location(self.span),
+        ))
     }
 }
 
-impl std::error::Error for LexError {}
+// Текст, из которого получены лексемы: вместе с пробелами вокруг них лексемы
+// покрывают исходный текст целиком
+pub fn source_text<'a>(lexems: impl IntoIterator<Item = &'a Lexem>) -> String {
+    let mut out = String::new();
+    for lexem in lexems {
+        for trivia in &lexem.leading {
+            out.push_str(&trivia.text);
+        }
+        out.push_str(lexem.text());
+        for trivia in &lexem.trailing {
+            out.push_str(&trivia.text);
+        }
+    }
+    out
+}
 
+// Результат разбора с восстановлением: лексемы (недопустимые символы заменены
+// лексемой Error) и все найденные ошибки
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexOutcome {
+    pub lexems: Vec<Lexem>,
+    pub diagnostics: Vec<Diagnostic>,
+}
 
 pub struct Lexer {
     idx: usize,
-    line: usize,
-    column: usize,
+    // Смещение текущего символа в байтах
+    offset: usize,
     chars: Vec<char>,
+    // Строки разбираемого текста: позиции в сообщениях вычисляются по смещениям
+    lines: LineIndex,
+    warn_mixed_scripts: bool,
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Lexer {
     pub fn new() -> Lexer {
         Lexer {
             idx: 0,
-            line: 1,
-            column: 1,
+            offset: 0,
             chars: vec![],
+            lines: LineIndex::default(),
+            warn_mixed_scripts: false,
         }
     }
-    pub fn lex(&mut self, contents: &str) -> Result<Vec<Lexem>, LexError> {
+
+    // Предупреждать о словах, смешивающих письменности (например, латинскую 'A'
+    // и кириллическую 'А'): такие имена выглядят одинаково, но различаются
+    pub fn warn_mixed_scripts(mut self, enabled: bool) -> Self {
+        self.warn_mixed_scripts = enabled;
+        self
+    }
+    // Разбор на лексемы; возвращает первую лексическую ошибку (предупреждения не мешают)
+    pub fn lex(&mut self, contents: &str) -> Result<Vec<Lexem>, Diagnostic> {
+        let outcome = self.lex_recovering(contents);
+        match outcome.diagnostics.into_iter().find(Diagnostic::is_error) {
+            Some(diagnostic) => Err(diagnostic),
+            None => Ok(outcome.lexems),
+        }
+    }
+
+    // Разбор на лексемы с восстановлением: каждая последовательность недопустимых
+    // символов становится одной лексемой Error с одной ошибкой, разбор продолжается
+    pub fn lex_recovering(&mut self, contents: &str) -> LexOutcome {
         self.idx = 0;
-        self.line = 1;
-        self.column = 1;
+        self.offset = 0;
         self.chars = contents.chars().collect();
+        self.lines = LineIndex::new(contents);
 
         let mut parsed_lexems = Vec::new();
+        let mut diagnostics = Vec::new();
 
-        while let Some(ch) = self.current_char() {
-            if ch.is_ascii_whitespace() {
-                self.advance();
-                continue;
-            }
+        loop {
+            let leading = self.trivia(false, &mut diagnostics);
+            let Some(ch) = self.current_char() else {
+                let mut eof = Lexem::new(
+                    LexemKind::Eof,
+                    String::new(),
+                    Span::new(self.offset, self.offset),
+                );
+                eof.leading = leading;
+                parsed_lexems.push(eof);
+                break;
+            };
 
-            match ch {
-                '(' => {
-                    parsed_lexems.push(self.make_lexem(LexemKind::LParen));
-                    self.advance();
+            let mut lexem = match ch {
+                '(' => self.single(LexemKind::LParen),
+                ')' => self.single(LexemKind::RParen),
+                ';' => self.single(LexemKind::Semicolon),
+                ',' => self.single(LexemKind::Comma),
+                ':' => self.single(LexemKind::Colon),
+                '-' => self.single(LexemKind::Minus),
+                '?' => self.single(LexemKind::Question),
+                c if c.is_ascii_digit() => {
+                    let (lexem, error) = self.integer();
+                    diagnostics.extend(error);
+                    lexem
                 }
-                ')' => {
-                    parsed_lexems.push(self.make_lexem(LexemKind::RParen));
-                    self.advance();
+                '"' | '\'' => {
+                    let (lexem, errors) = self.string(ch);
+                    diagnostics.extend(errors);
+                    lexem
                 }
-                ';' => {
-                    parsed_lexems.push(self.make_lexem(LexemKind::Semicolon));
-                    self.advance();
+                // Слово по правилам Unicode: XID_Start или '_' (для переменных в стиле
+                // Пролога), затем XID_Continue. Слова сравниваются после приведения к NFC.
+                c if starts_word(c) => {
+                    let (idx, start) = (self.idx, self.offset);
+                    while self.current_char().is_some_and(UnicodeXID::is_xid_continue) {
+                        self.advance();
+                    }
+                    let text: String = self.chars[idx..self.idx].iter().collect();
+                    let word: String = text.nfc().collect();
+                    let span = Span::new(start, self.offset);
+                    if self.warn_mixed_scripts {
+                        diagnostics.extend(mixed_scripts(&word, self.lines.location(span)));
+                    }
+                    let kind = match word.as_str() {
+                        "declare" => LexemKind::Declare,
+                        "conclusion" => LexemKind::Conclusion,
+                        "query" => LexemKind::Query,
+                        "not" => LexemKind::Not,
+                        "vars" => LexemKind::Vars,
+                        _ => LexemKind::Word(word),
+                    };
+                    Lexem::new(kind, text, span)
                 }
-                ',' => {
-                    parsed_lexems.push(self.make_lexem(LexemKind::Comma));
+                _ => {
+                    let lexem = self.error_run();
+                    diagnostics.extend(lexem.diagnostic(&self.lines));
+                    lexem
+                }
+            };
+            lexem.leading = leading;
+            lexem.trailing = self.trivia(true, &mut diagnostics);
+            parsed_lexems.push(lexem);
+        }
+
+        LexOutcome {
+            lexems: parsed_lexems,
+            diagnostics,
+        }
+    }
+
+    // Пробелы и комментарии перед лексемой; в пределах строки (same_line) — только
+    // до перевода строки, он достаётся следующей лексеме
+    fn trivia(&mut self, same_line: bool, diagnostics: &mut Vec<Diagnostic>) -> Vec<Trivia> {
+        let mut trivia = Vec::new();
+        loop {
+            let (idx, start) = (self.idx, self.offset);
+            let kind = match (self.current_char(), self.peek_char()) {
+                (Some(ch), _) if is_space(ch, same_line) => {
+                    while self
+                        .current_char()
+                        .is_some_and(|ch| is_space(ch, same_line))
+                    {
+                        self.advance();
+                    }
+                    TriviaKind::Whitespace
+                }
+                (Some('%'), _) | (Some('/'), Some('/')) => {
+                    while self.current_char().is_some_and(|ch| ch != '\n') {
+                        self.advance();
+                    }
+                    TriviaKind::LineComment
+                }
+                (Some('/'), Some('*')) => {
+                    diagnostics.extend(self.block_comment());
+                    TriviaKind::BlockComment
+                }
+                _ => break,
+            };
+            trivia.push(Trivia {
+                kind,
+                text: self.chars[idx..self.idx].iter().collect(),
+                span: Span::new(start, self.offset),
+            });
+        }
+        trivia
+    }
+
+    // Блочный комментарий с вложенными '/* */'; незакрытый комментарий продолжается
+    // до конца файла, а ошибка указывает на его начало
+    fn block_comment(&mut self) -> Option<Diagnostic> {
+        let opener = self.lines.location(Span::new(self.offset, self.offset + 2));
+        let mut depth = 0;
+        loop {
+            match (self.current_char(), self.peek_char()) {
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.advance();
                     self.advance();
                 }
-                ':' => {
-                    parsed_lexems.push(self.make_lexem(LexemKind::Colon));
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.advance();
                     self.advance();
+                    if depth == 0 {
+                        return None;
+                    }
+                }
+                (Some(_), _) => self.advance(),
+                (None, _) => {
+                    return Some(
+                        Diagnostic::error(
+                            codes::UNTERMINATED_COMMENT,
+                            "Unterminated block comment",
+                            opener,
+                        )
+                        .with_help("close the comment with '*/'"),
+                    );
                 }
-                '-' => {
-                    parsed_lexems.push(self.make_lexem(LexemKind::Minus));
+            }
+        }
+    }
+
+    // Целое число из цифр ASCII; при переполнении i64 значение заменяется нулём
+    fn integer(&mut self) -> (Lexem, Option<Diagnostic>) {
+        let (idx, start) = (self.idx, self.offset);
+        while self.current_char().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.advance();
+        }
+        let text: String = self.chars[idx..self.idx].iter().collect();
+        let span = Span::new(start, self.offset);
+        let location = self.lines.location(span);
+        let (value, error) = match text.parse::<i64>() {
+            Ok(value) => (value, None),
+            Err(_) => (
+                0,
+                Some(
+                    Diagnostic::error(
+                        codes::INTEGER_OVERFLOW,
+                        format!("Integer literal {} is too large", text),
+                        location,
+                    )
+                    .with_help(format!("integers must not exceed {}", i64::MAX)),
+                ),
+            ),
+        };
+        (Lexem::new(LexemKind::Integer(value), text, span), error)
+    }
+
+    // Строка в кавычках quote. Допустимые escape-последовательности: \\ \' \" \n \t \r \0
+    // и \u{XXXX}. Незакрытая строка заканчивается в конце строки текста,
+    // ошибка указывает на открывающую кавычку.
+    fn string(&mut self, quote: char) -> (Lexem, Vec<Diagnostic>) {
+        let (idx, start) = (self.idx, self.offset);
+        let mut value = String::new();
+        let mut errors = Vec::new();
+        self.advance();
+        loop {
+            match self.current_char() {
+                Some(ch) if ch == quote => {
                     self.advance();
+                    break;
                 }
-                c if c.is_ascii_alphabetic() => {
-                    let line = self.line;
-                    let column = self.column;
-                    let mut word = String::new();
-                    while let Some(c2) = self.current_char() {
-                        if c2.is_ascii_alphabetic() {
-                            word.push(c2);
-                            self.advance();
-                        } else {
-                            break;
-                        }
+                Some('\\') => {
+                    let end = self.offset + 1 + self.peek_char().map_or(0, char::len_utf8);
+                    let escape = self.lines.location(Span::new(self.offset, end));
+                    self.advance();
+                    match self.escape() {
+                        Some(ch) => value.push(ch),
+                        None => errors.push(
+                            Diagnostic::error(
+                                codes::INVALID_ESCAPE,
+                                "Invalid escape sequence in string",
+                                escape,
+                            )
+                            .with_help("valid escapes are \\\\, \\', \\\", \\n, \\t, \\r, \\0 and \\u{...}"),
+                        ),
                     }
-                    let kind = match word.as_str() {
-                        "declare" => LexemKind::Declare,
-                        "conclusion" => LexemKind::Conclusion,
-                        _ => LexemKind::Word(word),
-                    };
-                    parsed_lexems.push(Lexem { kind, line, column });
                 }
-                _ => {
-                    return Err(LexError {
-                        message: format!("Unexpected character '{}'", ch),
-                        line: self.line,
-                        column: self.column,
-                    });
+                Some('\n') | None => {
+                    errors.push(
+                        Diagnostic::error(
+                            codes::UNTERMINATED_STRING,
+                            "Unterminated string",
+                            self.lines.location(Span::new(start, start + 1)),
+                        )
+                        .with_help(format!("close the string with {}", quote)),
+                    );
+                    break;
+                }
+                Some(ch) => {
+                    value.push(ch);
+                    self.advance();
                 }
             }
         }
+        let text: String = self.chars[idx..self.idx].iter().collect();
+        let span = Span::new(start, self.offset);
+        (Lexem::new(LexemKind::Str(value), text, span), errors)
+    }
 
-        parsed_lexems.push(Lexem {
-            kind: LexemKind::Eof,
-            line: self.line,
-            column: self.column,
-        });
+    // Символ escape-последовательности после '\\'; None, если последовательность неверна
+    fn escape(&mut self) -> Option<char> {
+        let ch = self.current_char()?;
+        if ch == '\n' {
+            return None;
+        }
+        self.advance();
+        match ch {
+            '\\' | '\'' | '"' => Some(ch),
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            'u' => {
+                if self.current_char() != Some('{') {
+                    return None;
+                }
+                self.advance();
+                let mut digits = String::new();
+                while let Some(ch) = self.current_char().filter(char::is_ascii_hexdigit) {
+                    digits.push(ch);
+                    self.advance();
+                }
+                if self.current_char() != Some('}') {
+                    return None;
+                }
+                self.advance();
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+            }
+            _ => None,
+        }
+    }
 
-        Ok(parsed_lexems)
+    // Недопустимые символы до пробела, комментария или начала допустимой лексемы
+    fn error_run(&mut self) -> Lexem {
+        let start = self.offset;
+        let mut run = String::new();
+        while let Some(ch) = self.current_char() {
+            if ch.is_ascii_whitespace() || starts_lexem(ch) || self.at_comment() {
+                break;
+            }
+            run.push(ch);
+            self.advance();
+        }
+        Lexem::new(
+            LexemKind::Error(run.clone()),
+            run,
+            Span::new(start, self.offset),
+        )
     }
 
     fn current_char(&self) -> Option<char> {
         self.chars.get(self.idx).copied()
     }
 
+    fn peek_char(&self) -> Option<char> {
+        self.chars.get(self.idx + 1).copied()
+    }
+
+    fn at_comment(&self) -> bool {
+        matches!(
+            (self.current_char(), self.peek_char()),
+            (Some('%'), _) | (Some('/'), Some('/' | '*'))
+        )
+    }
+
     fn advance(&mut self) {
         if let Some(ch) = self.current_char() {
             self.idx += 1;
-            if ch == '\n' {
-                self.line += 1;
-                self.column = 1;
-            } else {
-                self.column += 1;
-            }
+            self.offset += ch.len_utf8();
         }
     }
 
-    fn make_lexem(&self, kind: LexemKind) -> Lexem {
-        Lexem {
+    // Лексема из одного символа
+    fn single(&mut self, kind: LexemKind) -> Lexem {
+        let lexem = Lexem::new(
             kind,
-            line: self.line,
-            column: self.column,
+            self.chars[self.idx].to_string(),
+            Span::new(self.offset, self.offset + 1),
+        );
+        self.advance();
+        lexem
+    }
+}
+
+fn is_space(ch: char, same_line: bool) -> bool {
+    if same_line {
+        ch == ' ' || ch == '\t'
+    } else {
+        ch.is_ascii_whitespace()
+    }
+}
+
+fn starts_word(ch: char) -> bool {
+    ch.is_xid_start() || ch == '_'
+}
+
+fn starts_lexem(ch: char) -> bool {
+    matches!(ch, '(' | ')' | ';' | ',' | ':' | '-' | '?' | '"' | '\'')
+        || ch.is_ascii_digit()
+        || starts_word(ch)
+}
+
+// Предупреждение, если в слове встречаются буквы разных письменностей;
+// цифры, '_' и другие общие символы письменности не имеют
+fn mixed_scripts(word: &str, location: Location) -> Option<Diagnostic> {
+    let mut scripts: Vec<Script> = Vec::new();
+    for ch in word.chars() {
+        let script = ch.script();
+        if !matches!(script, Script::Common | Script::Inherited | Script::Unknown)
+            && !scripts.contains(&script)
+        {
+            scripts.push(script);
         }
     }
+    if scripts.len() < 2 {
+        return None;
+    }
+
+    let names: Vec<_> = scripts.iter().map(|script| script.full_name()).collect();
+    Some(
+        Diagnostic::warning(
+            codes::MIXED_SCRIPT,
+            format!(
+                "Identifier '{}' mixes {} scripts",
+                word,
+                names.join(" and ")
+            ),
+            location,
+        )
+        .with_help("letters from different scripts may look identical; use one script per name"),
+    )
 }
@@ -1,3 +1,4 @@
+use crate::diagnostic::codes;
 use crate::lexer::*;
 
 fn lex(input: &str) -> Vec<Lexem> {
@@ -37,4 +38,285 @@ fn test_lex_error_unknown_char() {
     let mut lexer = Lexer::new();
     let error = lexer.lex(input).expect_err("expected lex error");
     assert!(error.message.contains("Unexpected character"));
+    assert_eq!(error.code, codes::UNEXPECTED_CHARACTER);
+    assert_eq!((error.location.line, error.location.column), (1, 17));
+}
+
+#[test]
+fn test_lex_query_tokens() {
+    let lexems = lex("?- A(x) ; query B(y)");
+    let kinds: Vec<_> = lexems.into_iter().map(|l| l.kind).collect();
+    assert_eq!(kinds[0], LexemKind::Question);
+    assert_eq!(kinds[1], LexemKind::Minus);
+    assert!(kinds.contains(&LexemKind::Query));
+}
+
+#[test]
+fn test_lex_not_keyword() {
+    let lexems = lex("not B(x) ; notB");
+    assert_eq!(lexems[0].kind, LexemKind::Not);
+    assert!(
+        lexems
+            .iter()
+            .any(|l| l.kind == LexemKind::Word("notB".to_string()))
+    );
+}
+
+#[test]
+fn test_lex_vars_keyword_and_underscore_words() {
+    let lexems = lex("vars _rest, snake_case");
+    let kinds: Vec<_> = lexems.into_iter().map(|l| l.kind).collect();
+    assert_eq!(kinds[0], LexemKind::Vars);
+    assert_eq!(kinds[1], LexemKind::Word("_rest".to_string()));
+    assert_eq!(kinds[3], LexemKind::Word("snake_case".to_string()));
+}
+
+#[test]
+fn test_lex_recovering_reports_every_bad_run() {
+    let outcome = Lexer::new().lex_recovering("declare Q(A#n) @@ ;\ndeclare B($)");
+    let messages: Vec<_> = outcome
+        .diagnostics
+        .iter()
+        .map(|d| {
+            (
+                d.message.as_str(),
+                d.location.line,
+                d.location.column,
+                d.location.len,
+            )
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            ("Unexpected character '#'", 1, 12, 1),
+            ("Unexpected characters '@@'", 1, 16, 2),
+            ("Unexpected character '$'", 2, 11, 1),
+        ]
+    );
+    assert!(
+        outcome
+            .diagnostics
+            .iter()
+            .all(|d| d.code == codes::UNEXPECTED_CHARACTER)
+    );
+}
+
+#[test]
+fn test_lex_recovering_keeps_error_lexems_in_stream() {
+    let outcome = Lexer::new().lex_recovering("Q(A#n)");
+    assert_eq!(
+        outcome
+            .lexems
+            .into_iter()
+            .map(|l| l.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            LexemKind::Word("Q".to_string()),
+            LexemKind::LParen,
+            LexemKind::Word("A".to_string()),
+            LexemKind::Error("#".to_string()),
+            LexemKind::Word("n".to_string()),
+            LexemKind::RParen,
+            LexemKind::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_lex_byte_spans() {
+    let input = "declare Q(Ann) Ёж;";
+    let lexems = Lexer::new().lex_recovering(input).lexems;
+    let texts: Vec<_> = lexems.iter().flat_map(|l| l.span.text(input)).collect();
+    assert_eq!(texts, vec!["declare", "Q", "(", "Ann", ")", "Ёж", ";", ""]);
+    assert_eq!(
+        lexems[7].span,
+        crate::span::Span::new(input.len(), input.len())
+    );
+}
+
+#[test]
+fn test_lex_comments_are_trivia() {
+    let input = "% facts\ndeclare Q(Ann); // Ann is a Q\n/* rules */ conclusion A(x):-Q(x)";
+    let lexems = lex(input);
+    assert_eq!(
+        lexems.iter().map(|l| l.kind.clone()).collect::<Vec<_>>(),
+        lex("declare Q(Ann);\nconclusion A(x):-Q(x)")
+            .into_iter()
+            .map(|l| l.kind)
+            .collect::<Vec<_>>()
+    );
+
+    let comments: Vec<_> = lexems
+        .iter()
+        .flat_map(|l| l.leading.iter().chain(&l.trailing))
+        .filter(|t| t.kind != TriviaKind::Whitespace)
+        .map(|t| (t.kind, t.text.as_str()))
+        .collect();
+    assert_eq!(
+        comments,
+        vec![
+            (TriviaKind::LineComment, "% facts"),
+            (TriviaKind::LineComment, "// Ann is a Q"),
+            (TriviaKind::BlockComment, "/* rules */"),
+        ]
+    );
+
+    // Комментарий до конца строки остаётся за ';', следующая строка — перед 'conclusion'
+    let semicolon = &lexems[5];
+    assert_eq!(semicolon.kind, LexemKind::Semicolon);
+    assert_eq!(semicolon.trailing[1].text, "// Ann is a Q");
+    assert_eq!(lexems[6].leading[1].text, "/* rules */");
+}
+
+#[test]
+fn test_lex_nested_block_comment() {
+    let lexems = lex("/* outer /* inner */ still comment */ Q");
+    assert_eq!(lexems[0].kind, LexemKind::Word("Q".to_string()));
+    assert_eq!(
+        lexems[0].leading[0].text,
+        "/* outer /* inner */ still comment */"
+    );
+}
+
+#[test]
+fn test_lex_unterminated_block_comment_points_at_opener() {
+    let input = "declare Q(Ann);\n  /* open /* nested */\ndeclare B(Bob)";
+    let outcome = Lexer::new().lex_recovering(input);
+    assert_eq!(outcome.diagnostics.len(), 1);
+    let error = &outcome.diagnostics[0];
+    assert_eq!(error.code, codes::UNTERMINATED_COMMENT);
+    assert_eq!(
+        (
+            error.location.line,
+            error.location.column,
+            error.location.len
+        ),
+        (2, 3, 2)
+    );
+
+    // Комментарий поглощает остаток файла
+    let eof = outcome.lexems.last().unwrap();
+    assert_eq!(eof.kind, LexemKind::Eof);
+    assert!(outcome.lexems[5].trailing.is_empty());
+    assert_eq!(outcome.lexems.len(), 7);
+}
+
+#[test]
+fn test_lex_lone_slash_is_an_error() {
+    let outcome = Lexer::new().lex_recovering("Q / B % comment");
+    assert_eq!(outcome.diagnostics.len(), 1);
+    assert_eq!(outcome.diagnostics[0].message, "Unexpected character '/'");
+    assert_eq!(outcome.lexems.len(), 4);
+}
+
+#[test]
+fn test_lex_unicode_identifiers() {
+    let input = "declare Q(Имя); Q(имя_2)";
+    let lexems = lex(input);
+    assert_eq!(lexems[3].kind, LexemKind::Word("Имя".to_string()));
+    assert_eq!(lexems[8].kind, LexemKind::Word("имя_2".to_string()));
+    // Столбцы считаются в символах, а не в байтах
+    let lines = LineIndex::new(input);
+    assert_eq!(lines.line_col(lexems[5].span.start), (1, 15));
+    assert_eq!(lines.location(lexems[3].span).len, 3);
+}
+
+#[test]
+fn test_lex_normalizes_words_to_nfc() {
+    let decomposed = "Q(И\u{306}ван)";
+    let lexems = lex(decomposed);
+    assert_eq!(lexems[2].kind, LexemKind::Word("\u{419}ван".to_string()));
+    assert_eq!(lexems[2].kind, lex("Q(\u{419}ван)")[2].kind);
+    // Текст лексемы остаётся таким, как в исходном файле
+    assert_eq!(lexems[2].text(), "И\u{306}ван");
+    assert_eq!(
+        LineIndex::new(decomposed).line_col(lexems[3].span.start),
+        (1, 8)
+    );
+}
+
+#[test]
+fn test_lex_mixed_script_warning_is_opt_in() {
+    // Первая буква — кириллическая 'А'
+    let input = "declare Q(\u{410}nn)";
+    assert!(Lexer::new().lex_recovering(input).diagnostics.is_empty());
+
+    let mut lexer = Lexer::new().warn_mixed_scripts(true);
+    let outcome = lexer.lex_recovering(input);
+    assert_eq!(outcome.diagnostics.len(), 1);
+    let warning = &outcome.diagnostics[0];
+    assert!(!warning.is_error());
+    assert_eq!(warning.code, codes::MIXED_SCRIPT);
+    assert_eq!(
+        warning.message,
+        "Identifier '\u{410}nn' mixes Cyrillic and Latin scripts"
+    );
+    assert_eq!((warning.location.column, warning.location.len), (11, 3));
+
+    // Предупреждения не считаются лексическими ошибками
+    assert!(lexer.lex(input).is_ok());
+    assert!(lexer.lex_recovering("Q(Имя_1, Ann)").diagnostics.is_empty());
+}
+
+#[test]
+fn test_lex_digits_and_underscores_in_words() {
+    let lexems = lex("Item_42 _tmp x1");
+    assert_eq!(lexems[0].kind, LexemKind::Word("Item_42".to_string()));
+    assert_eq!(lexems[1].kind, LexemKind::Word("_tmp".to_string()));
+    assert_eq!(lexems[2].kind, LexemKind::Word("x1".to_string()));
+}
+
+#[test]
+fn test_lex_integer_literals() {
+    let lexems = lex("Q(42, 007)");
+    assert_eq!(lexems[2].kind, LexemKind::Integer(42));
+    assert_eq!(lexems[4].kind, LexemKind::Integer(7));
+    assert_eq!(lexems[4].text(), "007");
+
+    let outcome = Lexer::new().lex_recovering("Q(99999999999999999999)");
+    assert_eq!(outcome.diagnostics.len(), 1);
+    assert_eq!(outcome.diagnostics[0].code, codes::INTEGER_OVERFLOW);
+    assert_eq!(outcome.diagnostics[0].location.len, 20);
+}
+
+#[test]
+fn test_lex_quoted_strings_with_escapes() {
+    let lexems = lex(r#"B("New York", 'it\'s', "tab\there \"q\" \\ \u{44f}")"#);
+    assert_eq!(lexems[2].kind, LexemKind::Str("New York".to_string()));
+    assert_eq!(lexems[4].kind, LexemKind::Str("it's".to_string()));
+    assert_eq!(
+        lexems[6].kind,
+        LexemKind::Str("tab\there \"q\" \\ я".to_string())
+    );
+    assert_eq!(lexems[4].text(), r"'it\'s'");
+    // Комментарии внутри строки остаются текстом строки
+    assert_eq!(
+        lex("Q('% not a comment')")[2].kind,
+        LexemKind::Str("% not a comment".to_string())
+    );
+}
+
+#[test]
+fn test_lex_string_errors() {
+    let outcome = Lexer::new().lex_recovering("B(\"bad \\q escape\");\nQ('open\nQ(Ann)");
+    let errors: Vec<_> = outcome
+        .diagnostics
+        .iter()
+        .map(|d| (d.code, d.location.line, d.location.column))
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (codes::INVALID_ESCAPE, 1, 8),
+            (codes::UNTERMINATED_STRING, 2, 3),
+        ]
+    );
+    // Незакрытая строка заканчивается в конце строки текста
+    assert!(
+        outcome
+            .lexems
+            .iter()
+            .any(|l| l.kind == LexemKind::Word("Ann".to_string()))
+    );
 }
@@ -0,0 +1,13 @@
+pub mod analysis;
+pub mod config;
+pub mod cst;
+pub mod diagnostic;
+pub mod eval;
+pub mod lexer;
+pub mod parser;
+pub mod proof;
+pub mod query;
+pub mod render;
+pub mod span;
+pub mod stratify;
+pub mod unify;
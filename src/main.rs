@@ -1,33 +1,338 @@
-use crate::{lexer::Lexer, parser::Parser};
 use anyhow::{Context, Result};
-use std::{env::args, fs::read_to_string};
+use std::{
+    env::args,
+    fs::read_to_string,
+    io::{IsTerminal, stderr},
+};
+use translation::{
+    analysis::{Semantics, analyze},
+    config::{LanguageConfig, Variables},
+    diagnostic::{Diagnostic, codes},
+    eval::{Evaluator, Model, Truth},
+    lexer::Lexer,
+    parser::{Call, Declaration, Parser, Program, Value, is_anonymous},
+    query::{Mode, Resolver, Solution},
+    render::Renderer,
+    span::LineIndex,
+    unify::unify,
+};
 
-mod lexer;
-mod parser;
+// Параметры командной строки:
+// FILE [--why FACT] [--json] [--plain] [--well-founded] [--predicates NAME[/ARITY],...] [--any-predicate]
+//      [--variables fixed|prolog|declared] [--zero-arity] [--warn-mixed-scripts] [--max-errors N]
+struct Options {
+    filename: String,
+    why: Option<String>,
+    json: bool,
+    plain: bool,
+    warn_mixed_scripts: bool,
+    max_errors: Option<usize>,
+    semantics: Semantics,
+    config: LanguageConfig,
+}
 
-fn main() -> Result<()> {
-    let args: Vec<String> = args().collect();
-    if args.len() < 2 {
-        eprintln!("Filename was not provided!");
-        return Ok(());
+fn parse_args() -> Result<Option<Options>, String> {
+    let mut filename = None;
+    let mut why = None;
+    let mut json = false;
+    let mut plain = false;
+    let mut warn_mixed_scripts = false;
+    let mut max_errors = None;
+    let mut semantics = Semantics::Stratified;
+    let mut config = LanguageConfig::default();
+
+    let mut args = args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--why" => match args.next() {
+                Some(fact) => why = Some(fact),
+                None => return Err("Option --why requires a fact".to_string()),
+            },
+            "--json" => json = true,
+            "--plain" => plain = true,
+            "--warn-mixed-scripts" => warn_mixed_scripts = true,
+            "--max-errors" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => max_errors = Some(n),
+                _ => return Err("Option --max-errors requires a positive number".to_string()),
+            },
+            "--well-founded" => semantics = Semantics::WellFounded,
+            "--predicates" => match args.next() {
+                Some(list) => config = parse_predicates(config, &list)?,
+                None => return Err("Option --predicates requires a list of names".to_string()),
+            },
+            "--any-predicate" => config = config.any_capitalised(),
+            "--zero-arity" => config = config.zero_arity(true),
+            "--variables" => {
+                let variables = match args.next().as_deref() {
+                    Some("fixed") => Variables::default_fixed(),
+                    Some("prolog") => Variables::PrologStyle,
+                    Some("declared") => Variables::Declared,
+                    _ => {
+                        return Err(
+                            "Option --variables requires fixed, prolog or declared".to_string()
+                        );
+                    }
+                };
+                config = config.variables(variables);
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => filename = Some(arg),
+        }
     }
-    let filename = &args[1];
-    let contents = read_to_string(filename).context(format!("File: {}", filename))?;
 
-    let mut lexer = Lexer::new();
-    let tokens = match lexer.lex(&contents) {
-        Ok(tokens) => tokens,
+    Ok(filename.map(|filename| Options {
+        filename,
+        why,
+        json,
+        plain,
+        warn_mixed_scripts,
+        max_errors,
+        semantics,
+        config,
+    }))
+}
+
+// Список предикатов вида Parent/2,Person: имя и необязательная арность
+fn parse_predicates(config: LanguageConfig, list: &str) -> Result<LanguageConfig, String> {
+    let mut names = Vec::new();
+    let mut arities = Vec::new();
+    for entry in list.split(',').map(str::trim) {
+        let (name, arity) = match entry.split_once('/') {
+            Some((name, arity)) => {
+                let arity = arity
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid arity in --predicates: {}", entry))?;
+                (name, Some(arity))
+            }
+            None => (entry, None),
+        };
+        if name.is_empty() {
+            return Err(format!("Empty predicate name in --predicates: {}", list));
+        }
+        names.push(name.to_string());
+        if let Some(arity) = arity {
+            arities.push((name.to_string(), arity));
+        }
+    }
+
+    let config = config.predicates(names);
+    Ok(arities
+        .into_iter()
+        .fold(config, |config, (name, arity)| config.arity(name, arity)))
+}
+
+fn main() -> Result<()> {
+    let options = match parse_args() {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            eprintln!("Filename was not provided!");
+            return Ok(());
+        }
         Err(e) => {
-            eprintln!("Lexical error: {}", e);
+            eprintln!("{}", e);
             return Ok(());
         }
     };
+    let filename = &options.filename;
+    let contents = read_to_string(filename).context(format!("File: {}", filename))?;
+
+    // Лексические, синтаксические и семантические ошибки выводятся одинаково
+    // Ошибки лексера не прерывают разбор: парсер продолжает работу по лексемам Error,
+    // и все ошибки и предупреждения выводятся вместе в порядке их положения в тексте
+    let lexed = Lexer::new()
+        .warn_mixed_scripts(options.warn_mixed_scripts)
+        .lex_recovering(&contents);
+    let mut parser =
+        Parser::new(lexed.lexems, options.config.clone()).lexer_diagnostics(lexed.diagnostics);
+    if let Some(max_errors) = options.max_errors {
+        parser = parser.max_errors(max_errors);
+    }
+    let outcome = parser.parse_program_recovering();
+    let diagnostics = outcome.diagnostics;
+    if !diagnostics.is_empty() {
+        report(&options, filename, &contents, &diagnostics);
+    }
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return Ok(());
+    }
+    let program = outcome.program;
+    println!("Syntax analysis: success");
+
+    // Проверки семантического анализа идут по очереди, поэтому их диагностики
+    // упорядочиваются по положению в тексте, как и синтаксические
+    if let Err(mut diagnostics) = analyze(&program, options.semantics) {
+        diagnostics
+            .sort_by_key(|diagnostic| (diagnostic.location.line, diagnostic.location.column));
+        report(&options, filename, &contents, &diagnostics);
+        return Ok(());
+    }
+
+    if let Some(fact) = &options.why {
+        explain(&program, &contents, fact, &options);
+        return Ok(());
+    }
+
+    let queries: Vec<_> = program
+        .declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            Declaration::Query { goal, .. } => Some(goal),
+            _ => None,
+        })
+        .collect();
+
+    // В обоснованной семантике запросы решаются по вычисленной модели,
+    // так как резолюция требует стратифицируемой программы
+    if options.semantics == Semantics::WellFounded {
+        match Evaluator::new(&program)
+            .semantics(Semantics::WellFounded)
+            .evaluate()
+        {
+            Ok(model) if queries.is_empty() => {
+                for fact in model.facts() {
+                    println!("{}", fact);
+                }
+                for fact in model.undefined() {
+                    println!("{}: undefined", fact);
+                }
+            }
+            Ok(model) => {
+                for goal in queries {
+                    answer_from_model(&model, goal);
+                }
+            }
+            Err(diagnostic) => report(&options, filename, &contents, &[diagnostic]),
+        }
+        return Ok(());
+    }
+
+    // Без запросов выводится вся модель, иначе — ответы на запросы в порядке их следования
+    if queries.is_empty() {
+        match Evaluator::new(&program).evaluate() {
+            Ok(model) => {
+                for fact in model.facts() {
+                    println!("{}", fact);
+                }
+            }
+            Err(diagnostic) => report(&options, filename, &contents, &[diagnostic]),
+        }
+        return Ok(());
+    }
 
-    let mut parser = Parser::new(tokens);
-    match parser.parse_program() {
-        Ok(_) => println!("Syntax analysis: success"),
-        Err(e) => eprintln!("Syntax error: {}", e),
+    let resolver = Resolver::new(&program).mode(Mode::Tabled);
+    for goal in queries {
+        println!("?- {}", goal);
+        match resolver.solve(goal) {
+            Ok(solutions) if solutions.is_empty() => println!("false"),
+            Ok(solutions) => {
+                for solution in &solutions {
+                    println!("{}", format_solution(solution));
+                }
+            }
+            Err(diagnostic) => report(&options, filename, &contents, &[diagnostic]),
+        }
     }
 
     Ok(())
 }
+
+// Ответы на запрос из фактов модели; неопределённые ответы помечаются
+fn answer_from_model(model: &Model, goal: &Call) {
+    println!("?- {}", goal);
+    let mut answered = false;
+    for fact in model.facts().into_iter().chain(model.undefined()) {
+        if let Some(subst) = unify(goal, &fact) {
+            let solution: Solution = subst
+                .iter()
+                .filter(|(var, _)| !is_anonymous(var))
+                .map(|(var, value)| (var.to_string(), value.clone()))
+                .collect();
+            match model.truth(&fact) {
+                Truth::Undefined => println!("{} (undefined)", format_solution(&solution)),
+                _ => println!("{}", format_solution(&solution)),
+            }
+            answered = true;
+        }
+    }
+    if !answered {
+        println!("false");
+    }
+}
+
+// Вывод дерева доказательства для факта, переданного через --why
+fn explain(program: &Program, contents: &str, fact: &str, options: &Options) {
+    let source = fact;
+    let fact = Lexer::new()
+        .lex(source)
+        .and_then(|tokens| Parser::new(tokens, options.config.clone()).parse_goal());
+    let fact = match fact {
+        Ok(fact) => fact,
+        Err(diagnostic) => {
+            report(options, "<--why>", source, &[diagnostic]);
+            return;
+        }
+    };
+
+    // Объяснение строится только для ground-факта: переменные запроса совпали бы
+    // по имени с переменными правил
+    let variable = fact
+        .args
+        .iter()
+        .zip(&fact.arg_spans)
+        .find(|(arg, _)| matches!(arg, Value::Variable(_)));
+    if let Some((var, span)) = variable {
+        let diagnostic = Diagnostic::error(
+            codes::NON_GROUND_FACT,
+            format!(
+                "Fact {} must be ground, but {} is a variable",
+                fact.func, var
+            ),
+            LineIndex::new(source).location(*span),
+        )
+        .with_help("--why explains ground facts only; use '?-' to search for bindings");
+        report(options, "<--why>", source, &[diagnostic]);
+        return;
+    }
+
+    let model = match Evaluator::new(program)
+        .semantics(options.semantics)
+        .evaluate()
+    {
+        Ok(model) => model,
+        Err(diagnostic) => {
+            report(options, &options.filename, contents, &[diagnostic]);
+            return;
+        }
+    };
+
+    match model.proof(&fact) {
+        Some(proof) if options.json => println!("{}", proof.to_json(&program.lines)),
+        Some(proof) => print!("{}", proof.render_text(&program.lines)),
+        None if model.truth(&fact) == Truth::Undefined => {
+            println!("{} is undefined in the well-founded model", fact);
+        }
+        None => {
+            if let Some(report) = model.why_not(&fact) {
+                print!("{}", report.render_text(&program.lines));
+            }
+        }
+    }
+}
+
+// Диагностики с фрагментами исходного текста; цвет — только для терминала
+fn report(options: &Options, filename: &str, source: &str, diagnostics: &[Diagnostic]) {
+    let plain = options.plain || !stderr().is_terminal();
+    let renderer = Renderer::new(filename, source).plain(plain);
+    eprint!("{}", renderer.render_all(diagnostics));
+}
+
+fn format_solution(solution: &Solution) -> String {
+    if solution.is_empty() {
+        return "true".to_string();
+    }
+    solution
+        .iter()
+        .map(|(var, value)| format!("{} = {}", var, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,196 +1,689 @@
 #[cfg(test)]
 mod tests;
 
-use crate::lexer::{Lexem, LexemKind};
+use crate::config::{LanguageConfig, Variables};
+use crate::cst::{SyntaxKind, SyntaxNode, SyntaxOutcome, TreeBuilder, lower, lower_call};
+use crate::diagnostic::{Diagnostic, Location, codes};
+use crate::lexer::{Lexem, LexemKind, source_text};
+use crate::span::{LineIndex, Span};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ParseError {
-    pub message: String,
-    pub line: usize,
-    pub column: usize,
-}
-
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} at {}:{}", self.message, self.line, self.column)
-    }
-}
-
-impl std::error::Error for ParseError {}
+// lines — строки исходного текста программы: по ним позиции span переводятся
+// в строку и столбец для сообщений
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Program {
     pub declarations: Vec<Declaration>,
+    pub span: Span,
+    pub lines: LineIndex,
 }
 
+// span — байты декларации в исходном тексте (без завершающей ';')
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Declaration {
-    Declare { func: String, identifier: String },
-    Conclusion { left: Call, right: Vec<Call> },
+    Declare {
+        fact: Call,
+        span: Span,
+    },
+    Conclusion {
+        left: Call,
+        right: Vec<Literal>,
+        span: Span,
+    },
+    Query {
+        goal: Call,
+        span: Span,
+    },
+    Vars {
+        names: Vec<String>,
+        span: Span,
+    },
+    // Заглушка на месте декларации, которую не удалось разобрать;
+    // span охватывает пропущенные при восстановлении токены
+    Error {
+        span: Span,
+    },
 }
 
+impl Declaration {
+    pub fn span(&self) -> Span {
+        match self {
+            Declaration::Declare { span, .. }
+            | Declaration::Conclusion { span, .. }
+            | Declaration::Query { span, .. }
+            | Declaration::Vars { span, .. }
+            | Declaration::Error { span, .. } => *span,
+        }
+    }
+}
+
+// Результат разбора с восстановлением: программа, в которой неразобранные декларации
+// заменены заглушками Declaration::Error, и все найденные ошибки
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOutcome {
+    pub program: Program,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Сколько ошибок собирается по умолчанию, прежде чем разбор прекращается
+const DEFAULT_MAX_ERRORS: usize = 20;
+
+// span — байты всего вызова (пустой, если вызов построен программно), arg_spans — байты
+// каждого аргумента: значения Value позиций не хранят, поэтому они записываются в вызове.
+#[derive(Debug, Clone)]
 pub struct Call {
     pub func: String,
     pub args: Vec<Value>,
+    pub span: Span,
+    pub arg_spans: Vec<Span>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl Call {
+    pub fn new(func: impl Into<String>, args: Vec<Value>) -> Self {
+        Self {
+            func: func.into(),
+            args,
+            span: Span::default(),
+            arg_spans: Vec::new(),
+        }
+    }
+
+    // Тот же вызов (и те же позиции) с другими аргументами
+    pub fn with_args(&self, args: Vec<Value>) -> Self {
+        Self {
+            func: self.func.clone(),
+            args,
+            span: self.span,
+            arg_spans: self.arg_spans.clone(),
+        }
+    }
+}
+
+// Позиция не участвует в сравнении: A(x) в разных местах программы — один и тот же вызов
+impl PartialEq for Call {
+    fn eq(&self, other: &Self) -> bool {
+        self.func == other.func && self.args == other.args
+    }
+}
+
+impl Eq for Call {}
+
+impl std::hash::Hash for Call {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.func.hash(state);
+        self.args.hash(state);
+    }
+}
+
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        self.call == other.call && self.polarity == other.polarity
+    }
+}
+
+impl Eq for Literal {}
+
+impl std::hash::Hash for Literal {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.call.hash(state);
+        self.polarity.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Polarity {
+    Positive,
+    Negative,
+}
+
+// Литерал тела правила: вызов или его отрицание 'not K';
+// span включает 'not' и, как у вызова, не участвует в сравнении
+#[derive(Debug, Clone)]
+pub struct Literal {
+    pub call: Call,
+    pub polarity: Polarity,
+    pub span: Span,
+}
+
+impl Literal {
+    pub fn positive(call: Call) -> Self {
+        Self {
+            span: call.span,
+            call,
+            polarity: Polarity::Positive,
+        }
+    }
+
+    pub fn negative(call: Call) -> Self {
+        Self {
+            span: call.span,
+            call,
+            polarity: Polarity::Negative,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.polarity == Polarity::Negative
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Value {
-    Variable(char),
+    Variable(String),
     Identifier(String),
+    Integer(i64),
+    String(String),
+}
+
+// Анонимная переменная '_': каждое её вхождение — отдельная переменная.
+// При построении программы вхождения получают имена _?1, _?2, ...: '?' не может
+// входить в слово, поэтому они не пересекаются с переменными программы
+pub const ANONYMOUS_VAR: &str = "_";
+const ANONYMOUS_PREFIX: &str = "_?";
+
+impl Value {
+    // Вхождение анонимной переменной с номером n
+    pub fn anonymous(n: usize) -> Self {
+        Value::Variable(format!("{}{}", ANONYMOUS_PREFIX, n))
+    }
+}
+
+// Имя переменной — вхождение '_'; такие переменные не входят в ответы
+pub fn is_anonymous(var: &str) -> bool {
+    var.starts_with(ANONYMOUS_PREFIX)
+}
+
+impl std::fmt::Display for Call {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(", self.func)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", arg)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl std::fmt::Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.polarity {
+            Polarity::Positive => write!(f, "{}", self.call),
+            Polarity::Negative => write!(f, "not {}", self.call),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Variable(v) if is_anonymous(v) => write!(f, "{}", ANONYMOUS_VAR),
+            Value::Variable(v) => write!(f, "{}", v),
+            Value::Identifier(id) => write!(f, "{}", id),
+            Value::Integer(n) => write!(f, "{}", n),
+            // Строка печатается так, чтобы её можно было снова разобрать
+            Value::String(text) => {
+                write!(f, "\"")?;
+                for ch in text.chars() {
+                    match ch {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\t' => write!(f, "\\t")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\0' => write!(f, "\\0")?,
+                        c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+        }
+    }
 }
 
 pub struct Parser {
     tokens: Vec<Lexem>,
     idx: usize,
+    config: LanguageConfig,
+    // Переменные, объявленные через 'vars' в уже разобранной части программы
+    declared: Vec<String>,
+    max_errors: usize,
+    // Диагностики лексера: ошибки из них входят в тот же предел max_errors
+    lexer_diagnostics: Vec<Diagnostic>,
+    // Дерево без потерь: парсер строит только его, программа получается из дерева
+    tree: TreeBuilder,
+    // Строки разбираемого текста для позиций в сообщениях об ошибках
+    lines: LineIndex,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Lexem>) -> Self {
-        Self { tokens, idx: 0 }
+    pub fn new(tokens: Vec<Lexem>, config: LanguageConfig) -> Self {
+        let lines = LineIndex::new(&source_text(&tokens));
+        Self {
+            tokens,
+            idx: 0,
+            config,
+            declared: Vec::new(),
+            max_errors: DEFAULT_MAX_ERRORS,
+            lexer_diagnostics: Vec::new(),
+            tree: TreeBuilder::default(),
+            lines,
+        }
+    }
+
+    // Предел числа ошибок, после которого parse_program_recovering останавливается
+    pub fn max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors.max(1);
+        self
     }
 
-    // начало парсинга
+    // Диагностики, найденные лексером при разборе тех же лексем (LexOutcome::diagnostics).
+    // parse_program_recovering возвращает их вместе со своими в порядке положения в тексте
+    // и учитывает ошибки лексера в пределе max_errors.
+    pub fn lexer_diagnostics(mut self, diagnostics: Vec<Diagnostic>) -> Self {
+        self.lexer_diagnostics = diagnostics;
+        self
+    }
+
+    // начало парсинга; возвращает первую синтаксическую ошибку
     // S -> D ( ';' D )* EOF
-    pub fn parse_program(&mut self) -> Result<Program, ParseError> {
-        let mut declarations = Vec::new();
-        declarations.push(self.parse_declaration()?);
+    pub fn parse_program(&mut self) -> Result<Program, Diagnostic> {
+        let lexer_error = self.tokens.iter().find_map(|t| t.diagnostic(&self.lines));
+        if let Some(diagnostic) = lexer_error {
+            return Err(diagnostic);
+        }
+        let outcome = self.parse_program_recovering();
+        match outcome.diagnostics.into_iter().find(Diagnostic::is_error) {
+            Some(diagnostic) => Err(diagnostic),
+            None => Ok(outcome.program),
+        }
+    }
+
+    // Разбор всей программы с восстановлением после ошибок (panic mode):
+    // на ошибке токены пропускаются до ';' или до начала следующей декларации,
+    // вместо сломанной декларации в программу попадает Declaration::Error.
+    // Об ошибочных лексемах уже сообщил лексер, поэтому на них ошибка не дублируется.
+    pub fn parse_program_recovering(&mut self) -> ParseOutcome {
+        let (tree, diagnostics) = self.parse_tree();
+        ParseOutcome {
+            program: lower(&tree, &self.config),
+            diagnostics,
+        }
+    }
+
+    // Разбор с восстановлением, который возвращает и дерево без потерь: пробелы
+    // и пропущенные при восстановлении лексемы остаются в дереве
+    pub fn parse_syntax_tree(&mut self) -> SyntaxOutcome {
+        let (tree, diagnostics) = self.parse_tree();
+        SyntaxOutcome {
+            program: lower(&tree, &self.config),
+            tree,
+            diagnostics,
+        }
+    }
+
+    // Дерево всей программы и ошибки. Парсер только проверяет грамматику и строит дерево;
+    // Program строится по дереву в cst::lower.
+    fn parse_tree(&mut self) -> (SyntaxNode, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
 
         loop {
-            if self.match_kind(&LexemKind::Semicolon) {
-                declarations.push(self.parse_declaration()?);
-                continue;
+            let checkpoint = self.checkpoint();
+            match self.parse_declaration() {
+                Ok(kind) => {
+                    self.wrap(checkpoint, kind);
+                    if self.match_kind(&LexemKind::Semicolon) {
+                        continue;
+                    }
+                    if self.is_eof() {
+                        break;
+                    }
+                    let token = self.current();
+                    if !matches!(token.kind, LexemKind::Error(_)) {
+                        diagnostics.push(
+                            Diagnostic::error(
+                                codes::TRAILING_TOKENS,
+                                "Unexpected token after end of program",
+                                self.location(token),
+                            )
+                            .with_help("separate declarations with ';'"),
+                        );
+                    }
+                    // Лишние лексемы не образуют декларации и остаются в дереве
+                    // между узлами деклараций, как ';'
+                    self.skip_declaration();
+                }
+                Err(diagnostic) => {
+                    if !matches!(self.current().kind, LexemKind::Error(_)) {
+                        diagnostics.push(diagnostic);
+                    }
+                    self.skip_declaration();
+                    self.wrap_error(checkpoint);
+                }
+            }
+
+            if self.error_count(&diagnostics) >= self.max_errors || !self.synchronize() {
+                break;
+            }
+        }
+
+        // Лексемы после последней ошибки (если разбор остановлен пределом ошибок)
+        while self.idx < self.tokens.len() {
+            self.bump();
+        }
+
+        // Ошибки лексера и парсера вместе, не больше max_errors; предупреждения сохраняются
+        let mut errors = 0;
+        diagnostics.extend(self.lexer_diagnostics.iter().cloned());
+        diagnostics.sort_by_key(|d| (d.location.line, d.location.column));
+        diagnostics.retain(|d| {
+            errors += d.is_error() as usize;
+            !d.is_error() || errors <= self.max_errors
+        });
+
+        (std::mem::take(&mut self.tree).finish(), diagnostics)
+    }
+
+    // Пропуск токенов до точки синхронизации: ';' поглощается, ключевые слова
+    // остаются для следующей декларации. false, если достигнут конец файла.
+    fn synchronize(&mut self) -> bool {
+        self.skip_declaration();
+        match self.current().kind {
+            LexemKind::Eof => false,
+            LexemKind::Semicolon => {
+                self.bump();
+                true
             }
+            _ => true,
+        }
+    }
 
-            break;
+    // Пропуск остатка сломанной декларации до ';', начала следующей декларации
+    // ('declare', 'conclusion', '?', 'query', 'vars') или конца файла
+    fn skip_declaration(&mut self) {
+        while !matches!(
+            self.current().kind,
+            LexemKind::Eof
+                | LexemKind::Semicolon
+                | LexemKind::Declare
+                | LexemKind::Conclusion
+                | LexemKind::Question
+                | LexemKind::Query
+                | LexemKind::Vars
+        ) {
+            self.bump();
         }
+    }
+
+    // Разбор отдельной цели запроса, например A(x, Name)
+    // G -> K EOF
+    pub fn parse_goal(&mut self) -> Result<Call, Diagnostic> {
+        self.parse_call()?;
 
         if !self.is_eof() {
             let token = self.current();
-            return Err(ParseError {
-                message: "Unexpected token after end of program".to_string(),
-                line: token.line,
-                column: token.column,
-            });
+            return Err(Diagnostic::error(
+                codes::TRAILING_TOKENS,
+                "Unexpected token after goal",
+                self.location(token),
+            ));
         }
 
-        Ok(Program { declarations })
+        let tree = std::mem::take(&mut self.tree).finish();
+        let goal = tree
+            .nodes()
+            .next()
+            .and_then(|node| lower_call(node, &self.config));
+        Ok(goal.expect("parse_call wraps the goal in a Call node"))
     }
 
-    // Декларация может быть либо объявлением, либо заключением
-    // D -> 'declare' F '(' Identifier ')' | 'conclusion' K ':' '-' K (',' K)*
-    fn parse_declaration(&mut self) -> Result<Declaration, ParseError> {
+    // Декларация может быть объявлением, заключением или запросом; возвращается вид узла
+    // D -> 'declare' K | 'conclusion' K ':' '-' L (',' L)* | '?' '-' K | 'query' K
+    //    | 'vars' Identifier (',' Identifier)*
+    // (аргументы K в 'declare' — только константы C)
+    fn parse_declaration(&mut self) -> Result<SyntaxKind, Diagnostic> {
+        let start = self.current().clone();
+
         // 'declare' ветка
         if self.match_kind(&LexemKind::Declare) {
-            let func = self.parse_func()?;
-            self.expect_kind(&LexemKind::LParen, "Expected '(' after function")?;
-            let identifier = self.parse_identifier()?;
-            self.expect_kind(&LexemKind::RParen, "Expected ')' after identifier")?;
-            return Ok(Declaration::Declare { func, identifier });
+            self.parse_fact()?;
+            return Ok(SyntaxKind::Declare);
         }
 
         // 'conclusion' ветка
         if self.match_kind(&LexemKind::Conclusion) {
-            let left = self.parse_call()?;
+            self.parse_call()?;
             self.expect_kind(&LexemKind::Colon, "Expected ':' after left expression")?;
             self.expect_kind(&LexemKind::Minus, "Expected '-' after ':'")?;
-            let mut right = Vec::new();
-            right.push(self.parse_call()?);
+            self.parse_literal()?;
             while self.match_kind(&LexemKind::Comma) {
-                right.push(self.parse_call()?);
+                self.parse_literal()?;
             }
-            return Ok(Declaration::Conclusion { left, right });
+            return Ok(SyntaxKind::Conclusion);
+        }
+
+        // запрос: '?-' или 'query'
+        if self.match_kind(&LexemKind::Question) {
+            self.expect_kind(&LexemKind::Minus, "Expected '-' after '?'")?;
+            self.parse_call()?;
+            return Ok(SyntaxKind::Query);
+        }
+        if self.match_kind(&LexemKind::Query) {
+            self.parse_call()?;
+            return Ok(SyntaxKind::Query);
+        }
+
+        // объявление переменных: 'vars'
+        if self.match_kind(&LexemKind::Vars) {
+            if self.config.variable_convention() != &Variables::Declared {
+                return Err(Diagnostic::error(
+                    codes::VARS_NOT_ALLOWED,
+                    "'vars' declaration requires the declared variable convention",
+                    self.location(&start),
+                )
+                .with_help("configure the parser with Variables::Declared"));
+            }
+            let mut names = vec![self.parse_identifier()?];
+            while self.match_kind(&LexemKind::Comma) {
+                names.push(self.parse_identifier()?);
+            }
+            self.declared.extend(names);
+            return Ok(SyntaxKind::Vars);
         }
 
         let token = self.current();
-        Err(ParseError {
-            message: "Expected 'declare' or 'conclusion'".to_string(),
-            line: token.line,
-            column: token.column,
-        })
+        Err(Diagnostic::error(
+            codes::EXPECTED_TOKEN,
+            "Expected 'declare', 'conclusion', '?-' or 'query'",
+            self.location(token),
+        ))
+    }
+
+    // Парсинг литерала тела правила
+    // L -> 'not' K | K
+    fn parse_literal(&mut self) -> Result<(), Diagnostic> {
+        let checkpoint = self.checkpoint();
+        self.match_kind(&LexemKind::Not);
+        self.parse_call()?;
+        self.wrap(checkpoint, SyntaxKind::Literal);
+        Ok(())
     }
 
     // Парсинг вызова функции
-    // K -> F '(' V (',' V)* ')'
-    fn parse_call(&mut self) -> Result<Call, ParseError> {
+    // K -> F A
+    fn parse_call(&mut self) -> Result<(), Diagnostic> {
+        self.parse_call_with(|parser, _| parser.parse_value(), "arguments")
+    }
+
+    // Факт 'declare': вызов, все аргументы которого — константы
+    fn parse_fact(&mut self) -> Result<(), Diagnostic> {
+        self.parse_call_with(|parser, func| parser.parse_fact_value(func), "identifier")
+    }
+
+    // Имя функции и список аргументов; каждый аргумент разбирает parse_arg,
+    // которому передаётся имя функции
+    fn parse_call_with(
+        &mut self,
+        mut parse_arg: impl FnMut(&mut Self, &str) -> Result<(), Diagnostic>,
+        after: &str,
+    ) -> Result<(), Diagnostic> {
+        let start = self.current().clone();
+        let checkpoint = self.checkpoint();
         let func = self.parse_func()?;
-        self.expect_kind(&LexemKind::LParen, "Expected '(' after function")?;
+        let count = self.parse_arguments(|parser| parse_arg(parser, &func), after)?;
+        self.check_arity(&func, count, &start)?;
+        self.wrap(checkpoint, SyntaxKind::Call);
+        Ok(())
+    }
 
-        let mut args = Vec::new();
-        args.push(self.parse_value()?);
+    // Список аргументов после имени функции; возвращает число аргументов.
+    // Если LanguageConfig разрешает атомы без аргументов, список может быть пустым '()'
+    // или отсутствовать вовсе.
+    // A -> '(' V (',' V)* ')' | '(' ')' | ε
+    fn parse_arguments(
+        &mut self,
+        mut parse_arg: impl FnMut(&mut Self) -> Result<(), Diagnostic>,
+        after: &str,
+    ) -> Result<usize, Diagnostic> {
+        let zero_arity = self.config.allows_zero_arity();
+        if zero_arity && self.current().kind != LexemKind::LParen {
+            return Ok(0);
+        }
 
-        while self.match_kind(&LexemKind::Comma) {
-            args.push(self.parse_value()?);
+        self.expect_kind(&LexemKind::LParen, "Expected '(' after function")?;
+        if zero_arity && self.match_kind(&LexemKind::RParen) {
+            return Ok(0);
         }
+        let mut count = 0;
+        loop {
+            parse_arg(self)?;
+            count += 1;
+            if !self.match_kind(&LexemKind::Comma) {
+                break;
+            }
+        }
+        self.expect_kind(&LexemKind::RParen, &format!("Expected ')' after {}", after))?;
+        Ok(count)
+    }
 
-        self.expect_kind(&LexemKind::RParen, "Expected ')' after arguments")?;
-        Ok(Call { func, args })
+    // Парсинг значения (переменной или константы); что считать переменной,
+    // задаёт LanguageConfig
+    // V -> Variable | C (по умолчанию Variable -> x | y | z)
+    fn parse_value(&mut self) -> Result<(), Diagnostic> {
+        if self.at_variable() {
+            self.bump();
+            return Ok(());
+        }
+        self.parse_constant()
     }
 
-    // Парсинг значения (идентификатора или переменной)
-    // V -> x | y | z | Identifier
-    fn parse_value(&mut self) -> Result<Value, ParseError> {
-        let word = self.parse_identifier()?;
-        if word == "x" || word == "y" || word == "z" {
-            Ok(Value::Variable(word.chars().next().unwrap()))
-        } else {
-            Ok(Value::Identifier(word))
+    // Аргумент факта: факт должен быть ground, поэтому переменная здесь — ошибка
+    fn parse_fact_value(&mut self, func: &str) -> Result<(), Diagnostic> {
+        let token = self.current();
+        if let LexemKind::Word(var) = &token.kind
+            && self.at_variable()
+        {
+            return Err(Diagnostic::error(
+                codes::NON_GROUND_FACT,
+                format!("Fact {} must be ground, but {} is a variable", func, var),
+                self.location(token),
+            )
+            .with_help("only constants may appear in 'declare'; use a conclusion for rules"));
         }
+        self.parse_value()
+    }
+
+    // Парсинг константы
+    // C -> Identifier | Integer | String
+    fn parse_constant(&mut self) -> Result<(), Diagnostic> {
+        match self.current().kind {
+            LexemKind::Word(_) | LexemKind::Integer(_) | LexemKind::Str(_) => {
+                self.bump();
+                Ok(())
+            }
+            _ => Err(Diagnostic::error(
+                codes::EXPECTED_TOKEN,
+                "Expected identifier, number or string",
+                self.location(self.current()),
+            )),
+        }
+    }
+
+    // Текущая лексема — переменная
+    fn at_variable(&self) -> bool {
+        matches!(&self.current().kind, LexemKind::Word(word) if self.config.is_variable(word, &self.declared))
     }
 
-    // Парсинг имени функции (Q, B или A)
-    // F -> 'Q' | 'B' | 'A'
-    fn parse_func(&mut self) -> Result<String, ParseError> {
+    // Парсинг имени функции; допустимые имена задаёт LanguageConfig
+    // F -> Word (по умолчанию 'Q' | 'B' | 'A')
+    fn parse_func(&mut self) -> Result<String, Diagnostic> {
         let token = self.current().clone();
         match &token.kind {
-            // Тип токена должен быть Word, а его значение должно быть Q, B или A
-            LexemKind::Word(w) if w == "Q" || w == "B" || w == "A" => {
-                self.idx += 1;
+            LexemKind::Word(w) if self.config.allows(w) => {
+                self.bump();
                 Ok(w.clone())
             }
-            _ => Err(ParseError {
-                message: "Expected function name: Q, B or A".to_string(),
-                line: token.line,
-                column: token.column,
-            }),
+            _ => Err(Diagnostic::error(
+                codes::UNKNOWN_FUNCTION,
+                format!(
+                    "Expected function name: {}",
+                    self.config.describe_predicates()
+                ),
+                self.location(&token),
+            )),
+        }
+    }
+
+    // Проверка арности, заданной в LanguageConfig; ошибка указывает на имя функции
+    fn check_arity(&self, func: &str, found: usize, at: &Lexem) -> Result<(), Diagnostic> {
+        match self.config.arity_of(func) {
+            Some(expected) if expected != found => Err(Diagnostic::error(
+                codes::CONFIGURED_ARITY,
+                format!(
+                    "Function {} expects {} argument(s), found {}",
+                    func, expected, found
+                ),
+                self.location(at),
+            )),
+            _ => Ok(()),
         }
     }
 
     // Парсинг идентификатора (любое слово, не являющееся ключевым)
-    // Identifier -> Word (кроме declare, conclusion, Q, B, A)
-    fn parse_identifier(&mut self) -> Result<String, ParseError> {
+    // Identifier -> Word (кроме ключевых слов declare, conclusion, query, not, vars)
+    fn parse_identifier(&mut self) -> Result<String, Diagnostic> {
         let token = self.current().clone();
         match token.kind {
             LexemKind::Word(w) => {
-                self.idx += 1;
+                self.bump();
                 Ok(w)
             }
-            _ => Err(ParseError {
-                message: "Expected identifier".to_string(),
-                line: token.line,
-                column: token.column,
-            }),
+            _ => Err(Diagnostic::error(
+                codes::EXPECTED_TOKEN,
+                "Expected identifier",
+                self.location(&token),
+            )),
         }
     }
 
     // Вспомогательная функция для проверки ожидаемого токена
-    fn expect_kind(&mut self, expected: &LexemKind, message: &str) -> Result<(), ParseError> {
+    fn expect_kind(&mut self, expected: &LexemKind, message: &str) -> Result<(), Diagnostic> {
         if self.match_kind(expected) {
             Ok(())
         } else {
             let token = self.current();
-            Err(ParseError {
-                message: message.to_string(),
-                line: token.line,
-                column: token.column,
-            })
+            Err(Diagnostic::error(
+                codes::EXPECTED_TOKEN,
+                message,
+                self.location(token),
+            ))
         }
     }
 
     // Вспомогательная функция для проверки и потребления ожидаемого токена
     fn match_kind(&mut self, expected: &LexemKind) -> bool {
         if self.current().kind == *expected {
-            self.idx += 1;
+            self.bump();
             true
         } else {
             false
@@ -204,6 +697,47 @@ impl Parser {
         &self.tokens[self.idx.min(self.tokens.len().saturating_sub(1))]
     }
 
+    // Позиция текущего токена
+    fn position(&self) -> (usize, usize) {
+        self.lines.line_col(self.current().span.start)
+    }
+
+    // Место лексемы для сообщения об ошибке; у конца файла длина нулевая
+    fn location(&self, token: &Lexem) -> Location {
+        self.lines.location(token.span)
+    }
+
+    // Переход к следующему токену; токен добавляется в дерево
+    fn bump(&mut self) {
+        if let Some(token) = self.tokens.get(self.idx) {
+            self.tree.token(token.clone());
+        }
+        self.idx += 1;
+    }
+
+    fn checkpoint(&self) -> usize {
+        self.tree.checkpoint()
+    }
+
+    fn wrap(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        self.tree.wrap(checkpoint, kind);
+    }
+
+    fn wrap_error(&mut self, checkpoint: usize) {
+        self.tree.wrap_error(checkpoint);
+    }
+
+    // Ошибки парсера и ошибки лексера до текущей лексемы
+    fn error_count(&self, diagnostics: &[Diagnostic]) -> usize {
+        let position = self.position();
+        let lexer = self
+            .lexer_diagnostics
+            .iter()
+            .filter(|d| d.is_error() && (d.location.line, d.location.column) < position)
+            .count();
+        diagnostics.len() + lexer
+    }
+
     fn is_eof(&self) -> bool {
         self.current().kind == LexemKind::Eof
     }
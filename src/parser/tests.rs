@@ -1,12 +1,16 @@
+use crate::config::{LanguageConfig, Variables};
+use crate::cst::SyntaxKind;
 use crate::lexer::Lexer;
-use crate::parser::{Declaration, Parser, Value};
+use crate::diagnostic::{Diagnostic, codes};
+use crate::parser::{Declaration, ParseOutcome, Parser, Polarity, Program, Value};
+use crate::span::{LineIndex, Span};
 
 #[test]
 fn test_parse_valid_program() {
 	let input = "declare A(Alpha); conclusion Q(x,y,Id):-B(z),A(Name)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let program = parser.parse_program().expect("parsing failed");
 	assert_eq!(program.declarations.len(), 2);
 }
@@ -16,7 +20,7 @@ fn test_parse_valid_multiple_semicolon() {
 	let input = "declare Q(Aa);declare B(Bb);conclusion A(x):-Q(y),B(z)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let program = parser.parse_program().expect("parsing failed");
 	assert_eq!(program.declarations.len(), 3);
 }
@@ -26,7 +30,7 @@ fn test_parse_error_multiple_newline_without_semicolon() {
 	let input = "declare Q(Aa)\ndeclare B(Bb)\nconclusion A(x):-Q(y),B(z)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error
 		.message
@@ -38,7 +42,7 @@ fn test_parse_error_missing_minus() {
 	let input = "conclusion Q(x): B(y)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected '-' after ':'"));
 }
@@ -48,7 +52,7 @@ fn test_parse_error_missing_rparen() {
 	let input = "declare Q(Name";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected ')' after identifier"));
 }
@@ -58,9 +62,12 @@ fn test_parse_error_unknown_start() {
 	let input = "hello Q(Name)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
-	assert!(error.message.contains("Expected 'declare' or 'conclusion'"));
+	assert_eq!(
+		error.message,
+		"Expected 'declare', 'conclusion', '?-' or 'query'"
+	);
 }
 
 #[test]
@@ -68,14 +75,17 @@ fn test_parse_valid_declare_ast_shape() {
 	let input = "declare Q(IdentifierOnly)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let program = parser.parse_program().expect("parsing failed");
 
 	assert_eq!(program.declarations.len(), 1);
 	match &program.declarations[0] {
-		Declaration::Declare { func, identifier } => {
-			assert_eq!(func, "Q");
-			assert_eq!(identifier, "IdentifierOnly");
+		Declaration::Declare { fact, .. } => {
+			assert_eq!(fact.func, "Q");
+			assert_eq!(
+				fact.args,
+				vec![Value::Identifier("IdentifierOnly".to_string())]
+			);
 		}
 		_ => panic!("expected declare declaration"),
 	}
@@ -86,22 +96,22 @@ fn test_parse_valid_conclusion_with_three_calls() {
 	let input = "conclusion A(x,y,Name):-Q(z),B(Id),A(AlphaBeta)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let program = parser.parse_program().expect("parsing failed");
 
 	assert_eq!(program.declarations.len(), 1);
 	match &program.declarations[0] {
-		Declaration::Conclusion { left, right } => {
+		Declaration::Conclusion { left, right, .. } => {
 			assert_eq!(left.func, "A");
 			assert_eq!(left.args.len(), 3);
-			assert_eq!(left.args[0], Value::Variable('x'));
-			assert_eq!(left.args[1], Value::Variable('y'));
+			assert_eq!(left.args[0], Value::Variable("x".to_string()));
+			assert_eq!(left.args[1], Value::Variable("y".to_string()));
 			assert_eq!(left.args[2], Value::Identifier("Name".to_string()));
 
 			assert_eq!(right.len(), 3);
-			assert_eq!(right[0].func, "Q");
-			assert_eq!(right[1].func, "B");
-			assert_eq!(right[2].func, "A");
+			assert_eq!(right[0].call.func, "Q");
+			assert_eq!(right[1].call.func, "B");
+			assert_eq!(right[2].call.func, "A");
 		}
 		_ => panic!("expected conclusion declaration"),
 	}
@@ -112,14 +122,14 @@ fn test_parse_valid_single_letter_identifier_not_variable() {
 	let input = "conclusion Q(a):-B(b),A(c)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let program = parser.parse_program().expect("parsing failed");
 
 	match &program.declarations[0] {
-		Declaration::Conclusion { left, right } => {
+		Declaration::Conclusion { left, right, .. } => {
 			assert_eq!(left.args[0], Value::Identifier("a".to_string()));
-			assert_eq!(right[0].args[0], Value::Identifier("b".to_string()));
-			assert_eq!(right[1].args[0], Value::Identifier("c".to_string()));
+			assert_eq!(right[0].call.args[0], Value::Identifier("b".to_string()));
+			assert_eq!(right[1].call.args[0], Value::Identifier("c".to_string()));
 		}
 		_ => panic!("expected conclusion declaration"),
 	}
@@ -130,7 +140,7 @@ fn test_parse_error_unknown_function_in_declare() {
 	let input = "declare C(Name)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected function name: Q, B or A"));
 }
@@ -140,7 +150,7 @@ fn test_parse_error_unknown_function_in_conclusion_right_side() {
 	let input = "conclusion Q(x):-C(y)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected function name: Q, B or A"));
 }
@@ -150,7 +160,7 @@ fn test_parse_error_missing_call_after_minus() {
 	let input = "conclusion Q(x):-";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected function name: Q, B or A"));
 }
@@ -160,7 +170,7 @@ fn test_parse_error_trailing_comma_in_arguments() {
 	let input = "conclusion Q(x,):-B(y)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected identifier"));
 }
@@ -170,7 +180,7 @@ fn test_parse_error_trailing_comma_in_right_calls() {
 	let input = "conclusion Q(x):-B(y),";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Expected function name: Q, B or A"));
 }
@@ -180,9 +190,12 @@ fn test_parse_error_trailing_semicolon() {
 	let input = "declare Q(Name);";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
-	assert!(error.message.contains("Expected 'declare' or 'conclusion'"));
+	assert_eq!(
+		error.message,
+		"Expected 'declare', 'conclusion', '?-' or 'query'"
+	);
 }
 
 #[test]
@@ -190,7 +203,7 @@ fn test_parse_error_unexpected_tokens_after_program() {
 	let input = "declare Q(Name) declare B(Other)";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
 	assert!(error.message.contains("Unexpected token after end of program"));
 }
@@ -200,7 +213,540 @@ fn test_parse_error_empty_input() {
 	let input = "";
 	let mut lexer = Lexer::new();
 	let tokens = lexer.lex(input).expect("lexing failed");
-	let mut parser = Parser::new(tokens);
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
 	let error = parser.parse_program().expect_err("expected parse error");
-	assert!(error.message.contains("Expected 'declare' or 'conclusion'"));
+	assert_eq!(
+		error.message,
+		"Expected 'declare', 'conclusion', '?-' or 'query'"
+	);
+}
+
+#[test]
+fn test_parse_goal() {
+	let input = "A(x, Name)";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	let goal = parser.parse_goal().expect("parsing failed");
+	assert_eq!(goal.func, "A");
+	assert_eq!(goal.args, vec![Value::Variable("x".to_string()), Value::Identifier("Name".to_string())]);
+}
+
+#[test]
+fn test_parse_error_goal_with_trailing_tokens() {
+	let input = "A(x) B(y)";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	let error = parser.parse_goal().expect_err("expected parse error");
+	assert!(error.message.contains("Unexpected token after goal"));
+}
+
+#[test]
+fn test_parse_valid_query_forms() {
+	let input = "declare Q(Alpha); ?- A(x, Alpha); query Q(y)";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	let program = parser.parse_program().expect("parsing failed");
+
+	assert_eq!(program.declarations.len(), 3);
+	match &program.declarations[1] {
+		Declaration::Query { goal, .. } => {
+			assert_eq!(goal.func, "A");
+			assert_eq!(goal.args[1], Value::Identifier("Alpha".to_string()));
+		}
+		_ => panic!("expected query declaration"),
+	}
+	match &program.declarations[2] {
+		Declaration::Query { goal, .. } => assert_eq!(goal.func, "Q"),
+		_ => panic!("expected query declaration"),
+	}
+}
+
+#[test]
+fn test_parse_error_question_without_minus() {
+	let input = "? A(x)";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	let error = parser.parse_program().expect_err("expected parse error");
+	assert!(error.message.contains("Expected '-' after '?'"));
+}
+
+#[test]
+fn test_parse_records_positions() {
+	let input = "declare Q(Alpha);\n  conclusion A(x):-Q(x)";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	let program = parser.parse_program().expect("parsing failed");
+
+	let lines = &program.lines;
+	assert_eq!(lines.line_col(program.declarations[0].span().start), (1, 1));
+	match &program.declarations[1] {
+		Declaration::Conclusion { left, right, .. } => {
+			assert_eq!(lines.line_col(left.span.start), (2, 14));
+			assert_eq!(lines.line_col(right[0].call.span.start), (2, 20));
+		}
+		_ => panic!("expected conclusion declaration"),
+	}
+}
+
+#[test]
+fn test_parse_valid_negated_literal() {
+	let input = "conclusion A(x):-Q(x), not B(x)";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	let program = parser.parse_program().expect("parsing failed");
+
+	match &program.declarations[0] {
+		Declaration::Conclusion { right, .. } => {
+			assert_eq!(right[0].polarity, Polarity::Positive);
+			assert_eq!(right[1].polarity, Polarity::Negative);
+			assert_eq!(right[1].call.func, "B");
+			assert_eq!(right[1].to_string(), "not B(x)");
+		}
+		_ => panic!("expected conclusion declaration"),
+	}
+}
+
+#[test]
+fn test_parse_error_not_without_call() {
+	let input = "conclusion A(x):-Q(x), not";
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, LanguageConfig::default());
+	assert!(parser.parse_program().is_err());
+}
+
+fn parse_with(input: &str, config: LanguageConfig) -> Result<Program, Diagnostic> {
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	let mut parser = Parser::new(tokens, config);
+	parser.parse_program()
+}
+
+#[test]
+fn test_parse_valid_configured_predicates() {
+	let config = LanguageConfig::new().predicates(["Parent", "Ancestor"]);
+	let program = parse_with(
+		"declare Parent(Ann); conclusion Ancestor(x, y):-Parent(x), Parent(y)",
+		config,
+	)
+	.expect("parsing failed");
+	assert_eq!(program.declarations.len(), 2);
+}
+
+#[test]
+fn test_parse_error_lists_configured_predicates() {
+	let config = LanguageConfig::new().predicates(["Parent", "Person", "Ancestor"]);
+	let error = parse_with("declare Q(Ann)", config).expect_err("expected parse error");
+	assert_eq!(
+		error.message,
+		"Expected function name: Parent, Person or Ancestor"
+	);
+	assert_eq!((error.location.line, error.location.column), (1, 9));
+	assert_eq!(error.code, codes::UNKNOWN_FUNCTION);
+}
+
+#[test]
+fn test_parse_valid_any_capitalised_predicate() {
+	let config = LanguageConfig::new().any_capitalised();
+	let program =
+		parse_with("declare Person(Ann); ?- Person(x)", config.clone()).expect("parsing failed");
+	assert_eq!(program.declarations.len(), 2);
+
+	let error = parse_with("declare person(Ann)", config).expect_err("expected parse error");
+	assert!(error.message.contains("any capitalised word"));
+}
+
+#[test]
+fn test_parse_error_configured_arity() {
+	let config = LanguageConfig::new()
+		.predicates(["Parent", "Person"])
+		.arity("Parent", 2);
+	let error = parse_with("conclusion Person(x):-Parent(x)", config.clone())
+		.expect_err("expected parse error");
+	assert_eq!(
+		error.message,
+		"Function Parent expects 2 argument(s), found 1"
+	);
+	assert_eq!((error.location.line, error.location.column), (1, 23));
+
+	let error = parse_with("declare Parent(Ann)", config).expect_err("expected parse error");
+	assert!(error.message.contains("expects 2 argument(s)"));
+}
+
+#[test]
+fn test_parse_valid_prolog_style_variables() {
+	let config = LanguageConfig::new().variables(Variables::PrologStyle);
+	let program =
+		parse_with("conclusion A(Who, _other, ann):-Q(Who)", config).expect("parsing failed");
+	match &program.declarations[0] {
+		Declaration::Conclusion { left, .. } => {
+			assert_eq!(left.args[0], Value::Variable("Who".to_string()));
+			assert_eq!(left.args[1], Value::Variable("_other".to_string()));
+			assert_eq!(left.args[2], Value::Identifier("ann".to_string()));
+		}
+		_ => panic!("expected conclusion declaration"),
+	}
+}
+
+#[test]
+fn test_parse_valid_declared_variables() {
+	let config = LanguageConfig::new().variables(Variables::Declared);
+	let program = parse_with(
+		"declare Q(w); vars w, v; conclusion A(w, v, x):-Q(w), B(v)",
+		config,
+	)
+	.expect("parsing failed");
+	assert_eq!(program.declarations.len(), 3);
+	match &program.declarations[0] {
+		Declaration::Declare { fact, .. } => {
+			assert_eq!(fact.args, vec![Value::Identifier("w".to_string())])
+		}
+		_ => panic!("expected declare declaration"),
+	}
+	match &program.declarations[1] {
+		Declaration::Vars { names, .. } => assert_eq!(names, &["w", "v"]),
+		_ => panic!("expected vars declaration"),
+	}
+	match &program.declarations[2] {
+		Declaration::Conclusion { left, .. } => {
+			assert_eq!(left.args[0], Value::Variable("w".to_string()));
+			assert_eq!(left.args[2], Value::Identifier("x".to_string()));
+		}
+		_ => panic!("expected conclusion declaration"),
+	}
+}
+
+#[test]
+fn test_parse_error_vars_without_declared_convention() {
+	let error = parse_with("vars w", LanguageConfig::default()).expect_err("expected parse error");
+	assert!(error.message.contains("'vars' declaration requires"));
+	assert_eq!((error.location.line, error.location.column), (1, 1));
+}
+
+fn parse_recovering(input: &str, max_errors: usize) -> ParseOutcome {
+	let mut lexer = Lexer::new();
+	let tokens = lexer.lex(input).expect("lexing failed");
+	Parser::new(tokens, LanguageConfig::default())
+		.max_errors(max_errors)
+		.parse_program_recovering()
+}
+
+#[test]
+fn test_parse_recovering_collects_all_errors() {
+	let input = "declare Q(Ann);\n\
+		declare R(Name);\n\
+		conclusion A(x):B(x);\n\
+		declare B(Bob)\n\
+		conclusion A(x):-Q(x)";
+	let outcome = parse_recovering(input, 20);
+	let messages: Vec<_> = outcome
+		.diagnostics
+		.iter()
+		.map(|d| d.message.as_str())
+		.collect();
+	assert_eq!(
+		messages,
+		vec![
+			"Expected function name: Q, B or A",
+			"Expected '-' after ':'",
+			"Unexpected token after end of program",
+		]
+	);
+
+	let declarations = &outcome.program.declarations;
+	assert_eq!(declarations.len(), 5);
+	assert!(matches!(declarations[0], Declaration::Declare { .. }));
+	assert_eq!(
+		declarations[1],
+		Declaration::Error {
+			span: Span::new(16, 31)
+		}
+	);
+	assert_eq!(
+		declarations[2],
+		Declaration::Error {
+			span: Span::new(33, 53)
+		}
+	);
+	assert!(matches!(declarations[3], Declaration::Declare { .. }));
+	assert!(matches!(declarations[4], Declaration::Conclusion { .. }));
+}
+
+#[test]
+fn test_parse_recovering_syncs_on_keyword_without_semicolon() {
+	let outcome = parse_recovering("declare Q(Ann) B(x) declare B(Bob)", 20);
+	assert_eq!(outcome.diagnostics.len(), 1);
+	assert_eq!(outcome.diagnostics[0].code, codes::TRAILING_TOKENS);
+	assert_eq!(outcome.program.declarations.len(), 2);
+}
+
+#[test]
+fn test_parse_recovering_syncs_on_query_and_vars() {
+	let config = LanguageConfig::default().variables(Variables::Declared);
+	let tokens = Lexer::new()
+		.lex("declare Q(Ann) B ?- Q(x); declare Q( query Q(x); declare B vars y")
+		.expect("lexing failed");
+	let outcome = Parser::new(tokens, config).parse_syntax_tree();
+	assert_eq!(outcome.diagnostics.len(), 3);
+	let kinds: Vec<_> = outcome.tree.nodes().map(|node| node.kind).collect();
+	assert_eq!(
+		kinds,
+		vec![
+			SyntaxKind::Declare,
+			SyntaxKind::Query,
+			SyntaxKind::Error,
+			SyntaxKind::Query,
+			SyntaxKind::Error,
+			SyntaxKind::Vars,
+		]
+	);
+}
+
+#[test]
+fn test_parse_recovering_respects_error_limit() {
+	let outcome = parse_recovering("hello; world; again; declare Q(Ann)", 2);
+	assert_eq!(outcome.diagnostics.len(), 2);
+	assert_eq!(outcome.program.declarations.len(), 2);
+}
+
+#[test]
+fn test_parse_recovering_counts_lexer_errors_toward_limit() {
+	let lexed =
+		Lexer::new().lex_recovering("declare Q(#); declare Q($); hello; world; declare Q(Ann)");
+	assert_eq!(lexed.diagnostics.len(), 2);
+	let outcome = Parser::new(lexed.lexems, LanguageConfig::default())
+		.lexer_diagnostics(lexed.diagnostics)
+		.max_errors(3)
+		.parse_program_recovering();
+	let codes: Vec<_> = outcome.diagnostics.iter().map(|d| d.code).collect();
+	assert_eq!(
+		codes,
+		vec![
+			codes::UNEXPECTED_CHARACTER,
+			codes::UNEXPECTED_CHARACTER,
+			codes::EXPECTED_TOKEN
+		]
+	);
+	assert_eq!(outcome.program.declarations.len(), 3);
+}
+
+#[test]
+fn test_parse_recovering_valid_program_has_no_diagnostics() {
+	let outcome = parse_recovering("declare Q(Ann); conclusion A(x):-Q(x)", 20);
+	assert!(outcome.diagnostics.is_empty());
+	assert_eq!(outcome.program.declarations.len(), 2);
+}
+
+#[test]
+fn test_parse_recovering_does_not_repeat_lexer_errors() {
+	let lexed = Lexer::new().lex_recovering("declare Q(A#n); declare R(Bob); declare B(Bob)");
+	assert_eq!(lexed.diagnostics.len(), 1);
+	let outcome = Parser::new(lexed.lexems, LanguageConfig::default()).parse_program_recovering();
+	let messages: Vec<_> = outcome
+		.diagnostics
+		.iter()
+		.map(|d| d.message.as_str())
+		.collect();
+	assert_eq!(messages, vec!["Expected function name: Q, B or A"]);
+	assert_eq!(outcome.program.declarations.len(), 3);
+	assert_eq!(
+		outcome.program.declarations[0],
+		Declaration::Error {
+			span: Span::new(0, 14)
+		}
+	);
+}
+
+#[test]
+fn test_parse_program_fails_on_error_lexem() {
+	let lexed = Lexer::new().lex_recovering("declare Q(Ann) $");
+	let error = Parser::new(lexed.lexems, LanguageConfig::default())
+		.parse_program()
+		.expect_err("expected error");
+	assert_eq!(error.code, codes::UNEXPECTED_CHARACTER);
+	assert_eq!((error.location.line, error.location.column), (1, 16));
+}
+
+#[test]
+fn test_parse_records_byte_spans() {
+	let input = "declare Q(Ann);\nconclusion A(x) :- Q(x), not B(x)";
+	let program = parse_with(input, LanguageConfig::default()).expect("parsing failed");
+	assert_eq!(program.span, Span::new(0, input.len()));
+
+	let declare = &program.declarations[0];
+	assert_eq!(declare.span().text(input), Some("declare Q(Ann)"));
+
+	let Declaration::Conclusion { left, right, span } = &program.declarations[1] else {
+		panic!("expected conclusion");
+	};
+	assert_eq!(span.text(input), Some("conclusion A(x) :- Q(x), not B(x)"));
+	assert_eq!(left.span.text(input), Some("A(x)"));
+	assert_eq!(left.arg_spans[0].text(input), Some("x"));
+	assert_eq!(right[0].span.text(input), Some("Q(x)"));
+	assert_eq!(right[1].span.text(input), Some("not B(x)"));
+	assert_eq!(right[1].call.span.text(input), Some("B(x)"));
+
+	let index = LineIndex::new(input);
+	assert_eq!(index, program.lines);
+	assert_eq!(index.line_col(right[1].span.start), (2, 26));
+	assert_eq!(index.location(left.span).len, 4);
+}
+
+#[test]
+fn test_parse_cyrillic_names() {
+	let config = LanguageConfig::default()
+		.any_capitalised()
+		.variables(Variables::PrologStyle);
+	let program = parse_with(
+		"declare Родитель(анна); conclusion Предок(X):-Родитель(X)",
+		config,
+	)
+	.expect("parsing failed");
+	let Declaration::Conclusion { left, .. } = &program.declarations[1] else {
+		panic!("expected conclusion");
+	};
+	assert_eq!(left.func, "Предок");
+	assert_eq!(left.args, vec![Value::Variable("X".to_string())]);
+}
+
+#[test]
+fn test_parse_integer_and_string_constants() {
+	let program = parse_with(
+		"declare B(\"New York\"); declare Q(42); conclusion A(x):-B(x), Q(7)",
+		LanguageConfig::default(),
+	)
+	.expect("parsing failed");
+	let values: Vec<_> = program
+		.declarations
+		.iter()
+		.filter_map(|declaration| match declaration {
+			Declaration::Declare { fact, .. } => Some(fact.args[0].clone()),
+			_ => None,
+		})
+		.collect();
+	assert_eq!(
+		values,
+		vec![Value::String("New York".to_string()), Value::Integer(42)]
+	);
+	let Declaration::Conclusion { right, .. } = &program.declarations[2] else {
+		panic!("expected conclusion");
+	};
+	assert_eq!(right[1].call.args, vec![Value::Integer(7)]);
+	assert_eq!(right[1].call.to_string(), "Q(7)");
+}
+
+#[test]
+fn test_string_value_display_round_trips() {
+	let value = Value::String("say \"hi\"\n\\".to_string());
+	assert_eq!(value.to_string(), r#""say \"hi\"\n\\""#);
+	let program = parse_with(&format!("declare B({})", value), LanguageConfig::default())
+		.expect("parsing failed");
+	match &program.declarations[0] {
+		Declaration::Declare { fact, .. } => assert_eq!(fact.args, vec![value]),
+		_ => panic!("expected declare declaration"),
+	}
+}
+
+#[test]
+fn test_parse_multi_argument_fact() {
+	let program =
+		parse_with("declare A(Alpha, Beta, 3)", LanguageConfig::default()).expect("parsing failed");
+	match &program.declarations[0] {
+		Declaration::Declare { fact, .. } => {
+			assert_eq!(fact.func, "A");
+			assert_eq!(
+				fact.args,
+				vec![
+					Value::Identifier("Alpha".to_string()),
+					Value::Identifier("Beta".to_string()),
+					Value::Integer(3),
+				]
+			);
+		}
+		_ => panic!("expected declare declaration"),
+	}
+}
+
+#[test]
+fn test_parse_fact_records_argument_spans() {
+	let input = "declare A(Alpha, 3)";
+	let program = parse_with(input, LanguageConfig::default()).expect("expected successful parse");
+	match &program.declarations[0] {
+		Declaration::Declare { fact, .. } => {
+			assert_eq!(program.lines.line_col(fact.span.start), (1, 9));
+			assert_eq!(fact.span.text(input), Some("A(Alpha, 3)"));
+			let texts: Vec<_> = fact
+				.arg_spans
+				.iter()
+				.flat_map(|span| span.text(input))
+				.collect();
+			assert_eq!(texts, vec!["Alpha", "3"]);
+		}
+		_ => panic!("expected declare declaration"),
+	}
+}
+
+#[test]
+fn test_parse_error_fact_must_be_ground() {
+	let error = parse_with("declare A(Alpha, x)", LanguageConfig::default())
+		.expect_err("expected parse error");
+	assert_eq!(error.code, codes::NON_GROUND_FACT);
+	assert_eq!(error.message, "Fact A must be ground, but x is a variable");
+	assert_eq!((error.location.line, error.location.column), (1, 18));
+}
+
+#[test]
+fn test_parse_error_fact_arity_from_config() {
+	let config = LanguageConfig::default().arity("A", 2);
+	let error = parse_with("declare A(Alpha)", config).expect_err("expected parse error");
+	assert_eq!(error.code, codes::CONFIGURED_ARITY);
+	assert_eq!(error.message, "Function A expects 2 argument(s), found 1");
+}
+
+#[test]
+fn test_parse_error_empty_arguments_by_default() {
+	let error =
+		parse_with("declare Q()", LanguageConfig::default()).expect_err("expected parse error");
+	assert_eq!(error.message, "Expected identifier, number or string");
+	assert_eq!((error.location.line, error.location.column), (1, 11));
+}
+
+#[test]
+fn test_parse_valid_zero_arity_atoms() {
+	let config = LanguageConfig::new().any_capitalised().zero_arity(true);
+	let program = parse_with(
+		"declare Ready(); declare Open; conclusion Go(x):-Q(x), Ready, not Closed()",
+		config,
+	)
+	.expect("expected successful parse");
+	match &program.declarations[..] {
+		[
+			Declaration::Declare { fact: ready, .. },
+			Declaration::Declare { fact: open, .. },
+			Declaration::Conclusion { left, right, .. },
+		] => {
+			assert_eq!((ready.func.as_str(), ready.args.len()), ("Ready", 0));
+			assert_eq!((open.func.as_str(), open.args.len()), ("Open", 0));
+			assert_eq!(left.to_string(), "Go(x)");
+			assert_eq!(
+				right.iter().map(ToString::to_string).collect::<Vec<_>>(),
+				vec!["Q(x)", "Ready()", "not Closed()"]
+			);
+		}
+		_ => panic!("expected two declares and a conclusion"),
+	}
+}
+
+#[test]
+fn test_parse_error_zero_arity_respects_configured_arity() {
+	let config = LanguageConfig::default().zero_arity(true).arity("Q", 1);
+	let error = parse_with("declare Q", config).expect_err("expected parse error");
+	assert_eq!(error.code, codes::CONFIGURED_ARITY);
+	assert_eq!(error.message, "Function Q expects 1 argument(s), found 0");
 }
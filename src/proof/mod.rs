@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Write;
+
+use crate::parser::{Call, Literal, is_anonymous};
+use crate::span::{LineIndex, Span};
+use crate::unify::Substitution;
+
+// Дерево доказательства выведенного факта
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub fact: Call,
+    pub step: ProofStep,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    // Факт объявлен через 'declare'; span — байты объявления
+    Declared {
+        span: Span,
+    },
+    // Литерал 'not K' выполнен: факт K не выведен
+    Absent,
+    // Факт выведен правилом head :- body при подстановке bindings;
+    // premises — доказательства литералов тела в том же порядке
+    Rule {
+        head: Call,
+        body: Vec<Literal>,
+        bindings: Substitution,
+        premises: Vec<Proof>,
+    },
+}
+
+// Позиции в выводе — строка и столбец по lines, строкам исходного текста программы
+impl Proof {
+    // Текстовое представление с отступом в два пробела на уровень
+    pub fn render_text(&self, lines: &LineIndex) -> String {
+        let mut out = String::new();
+        self.write_text(&mut out, lines, 0);
+        out
+    }
+
+    pub fn to_json(&self, lines: &LineIndex) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, lines);
+        out
+    }
+
+    fn write_text(&self, out: &mut String, lines: &LineIndex, depth: usize) {
+        let indent = "  ".repeat(depth);
+        match &self.step {
+            ProofStep::Declared { span } => {
+                let (line, column) = lines.line_col(span.start);
+                let _ = writeln!(
+                    out,
+                    "{}{} declared at {}:{}",
+                    indent, self.fact, line, column
+                );
+            }
+            ProofStep::Absent => {
+                let _ = writeln!(out, "{}not {}: not derivable", indent, self.fact);
+            }
+            ProofStep::Rule {
+                head,
+                body,
+                bindings,
+                premises,
+            } => {
+                let (line, column) = lines.line_col(head.span.start);
+                let _ = write!(
+                    out,
+                    "{}{} by conclusion {} at {}:{}",
+                    indent,
+                    self.fact,
+                    format_rule(head, body),
+                    line,
+                    column
+                );
+                let bindings = format_bindings(bindings);
+                if !bindings.is_empty() {
+                    let _ = write!(out, " with {}", bindings);
+                }
+                out.push('\n');
+                for premise in premises {
+                    premise.write_text(out, lines, depth + 1);
+                }
+            }
+        }
+    }
+
+    fn write_json(&self, out: &mut String, lines: &LineIndex) {
+        out.push_str("{\"fact\":");
+        write_json_string(out, &self.fact.to_string());
+        match &self.step {
+            ProofStep::Declared { span } => {
+                let (line, column) = lines.line_col(span.start);
+                let _ = write!(
+                    out,
+                    ",\"declared\":{{\"line\":{},\"column\":{}}}",
+                    line, column
+                );
+            }
+            ProofStep::Absent => out.push_str(",\"absent\":true"),
+            ProofStep::Rule {
+                head,
+                body,
+                bindings,
+                premises,
+            } => {
+                out.push_str(",\"rule\":{\"text\":");
+                write_json_string(out, &format_rule(head, body));
+                let (line, column) = lines.line_col(head.span.start);
+                let _ = write!(out, ",\"line\":{},\"column\":{}}}", line, column);
+
+                out.push_str(",\"bindings\":{");
+                let named = bindings.iter().filter(|(var, _)| !is_anonymous(var));
+                for (i, (var, value)) in named.enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_json_string(out, var);
+                    out.push(':');
+                    write_json_string(out, &value.to_string());
+                }
+                out.push('}');
+
+                out.push_str(",\"premises\":[");
+                for (i, premise) in premises.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    premise.write_json(out, lines);
+                }
+                out.push(']');
+            }
+        }
+        out.push('}');
+    }
+}
+
+// Отчёт о том, почему факт не выведен
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhyNot {
+    pub fact: Call,
+    // Правила, заключение которых унифицируется с фактом, в порядке объявления
+    pub attempts: Vec<RuleAttempt>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleAttempt {
+    pub head: Call,
+    pub body: Vec<Literal>,
+    pub failures: Vec<Failure>,
+}
+
+// При привязках bindings литерал тела с номером atom не выполнился:
+// положительный атом call не сопоставился ни с одним фактом,
+// либо отрицаемый факт call выведен
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub bindings: Substitution,
+    pub atom: usize,
+    pub call: Call,
+}
+
+impl WhyNot {
+    pub fn render_text(&self, lines: &LineIndex) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{} is not derivable", self.fact);
+        if self.attempts.is_empty() {
+            let _ = writeln!(out, "  no conclusion has a head matching {}", self.fact);
+        }
+
+        for attempt in &self.attempts {
+            let (line, column) = lines.line_col(attempt.head.span.start);
+            let _ = writeln!(
+                out,
+                "  conclusion {} at {}:{}",
+                format_rule(&attempt.head, &attempt.body),
+                line,
+                column
+            );
+            for failure in &attempt.failures {
+                let atom = &attempt.body[failure.atom];
+                let _ = write!(out, "    ");
+                let bindings = format_bindings(&failure.bindings);
+                if !bindings.is_empty() {
+                    let _ = write!(out, "with {}: ", bindings);
+                }
+                let reason = if atom.is_negative() {
+                    format!("{} holds", failure.call)
+                } else {
+                    format!("no fact matches {}", failure.call)
+                };
+                let (line, column) = lines.line_col(atom.call.span.start);
+                let _ = writeln!(
+                    out,
+                    "fails at body atom {} {} ({}:{}): {}",
+                    failure.atom + 1,
+                    atom,
+                    line,
+                    column,
+                    reason
+                );
+            }
+        }
+        out
+    }
+}
+
+// Правило в исходном синтаксисе: A(x, y) :- Q(x), not B(y)
+pub fn format_rule(head: &Call, body: &[Literal]) -> String {
+    let body = body
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} :- {}", head, body)
+}
+
+// Привязки переменных правила; анонимные переменные не показываются
+pub fn format_bindings(bindings: &Substitution) -> String {
+    bindings
+        .iter()
+        .filter(|(var, _)| !is_anonymous(var))
+        .map(|(var, value)| format!("{} = {}", var, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn write_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
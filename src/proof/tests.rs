@@ -0,0 +1,200 @@
+use crate::config::LanguageConfig;
+use crate::eval::{Evaluator, Model};
+use crate::lexer::Lexer;
+use crate::parser::{Call, Parser};
+use crate::proof::ProofStep;
+use crate::span::{LineIndex, Span};
+
+fn evaluate(input: &str) -> Model {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    let program = parser.parse_program().expect("parsing failed");
+    Evaluator::new(&program)
+        .evaluate()
+        .expect("evaluation failed")
+}
+
+fn fact(input: &str) -> Call {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    parser.parse_goal().expect("parsing fact failed")
+}
+
+const PROGRAM: &str = "declare Q(Alpha); declare B(Beta);\nconclusion A(x,y):-Q(x),B(y)";
+
+#[test]
+fn test_proof_of_declared_fact() {
+    let model = evaluate(PROGRAM);
+    let proof = model.proof(&fact("B(Beta)")).expect("expected proof");
+    assert_eq!(
+        proof.step,
+        ProofStep::Declared {
+            span: Span::new(18, 33)
+        }
+    );
+}
+
+#[test]
+fn test_proof_of_derived_fact() {
+    let model = evaluate(PROGRAM);
+    let proof = model
+        .proof(&fact("A(Alpha, Beta)"))
+        .expect("expected proof");
+    match &proof.step {
+        ProofStep::Rule {
+            head,
+            bindings,
+            premises,
+            ..
+        } => {
+            assert_eq!(LineIndex::new(PROGRAM).line_col(head.span.start), (2, 12));
+            assert_eq!(bindings.len(), 2);
+            assert_eq!(premises.len(), 2);
+            assert_eq!(premises[0].fact.to_string(), "Q(Alpha)");
+            assert_eq!(premises[1].fact.to_string(), "B(Beta)");
+        }
+        _ => panic!("expected rule step"),
+    }
+}
+
+#[test]
+fn test_proof_of_missing_fact() {
+    let model = evaluate(PROGRAM);
+    assert!(model.proof(&fact("A(Beta, Alpha)")).is_none());
+}
+
+#[test]
+fn test_proof_text_rendering() {
+    let model = evaluate(PROGRAM);
+    let proof = model
+        .proof(&fact("A(Alpha, Beta)"))
+        .expect("expected proof");
+    assert_eq!(
+        proof.render_text(&LineIndex::new(PROGRAM)),
+        "A(Alpha, Beta) by conclusion A(x, y) :- Q(x), B(y) at 2:12 with x = Alpha, y = Beta\n  \
+         Q(Alpha) declared at 1:1\n  \
+         B(Beta) declared at 1:19\n"
+    );
+}
+
+#[test]
+fn test_proof_json_rendering() {
+    let input = "declare Q(Alpha); conclusion A(x):-Q(x)";
+    let proof = evaluate(input)
+        .proof(&fact("A(Alpha)"))
+        .expect("expected proof");
+    assert_eq!(
+        proof.to_json(&LineIndex::new(input)),
+        "{\"fact\":\"A(Alpha)\",\
+         \"rule\":{\"text\":\"A(x) :- Q(x)\",\"line\":1,\"column\":30},\
+         \"bindings\":{\"x\":\"Alpha\"},\
+         \"premises\":[{\"fact\":\"Q(Alpha)\",\"declared\":{\"line\":1,\"column\":1}}]}"
+    );
+}
+
+#[test]
+fn test_proof_of_recursive_fact_is_finite() {
+    let input = "declare Q(Ann); declare Q(Bob); declare B(Bob); declare B(Cid); \
+         conclusion A(x,y):-Q(x),B(y); conclusion A(x,z):-A(x,y),A(y,z)";
+    let proof = evaluate(input)
+        .proof(&fact("A(Ann, Cid)"))
+        .expect("expected proof");
+    assert!(proof.render_text(&LineIndex::new(input)).lines().count() >= 3);
+}
+
+#[test]
+fn test_why_not_derivable_fact_is_none() {
+    let model = evaluate(PROGRAM);
+    assert!(model.why_not(&fact("A(Alpha, Beta)")).is_none());
+}
+
+#[test]
+fn test_why_not_reports_first_failing_atom() {
+    let model = evaluate(PROGRAM);
+    let report = model
+        .why_not(&fact("A(Beta, Beta)"))
+        .expect("expected report");
+    assert_eq!(report.attempts.len(), 1);
+    let failures = &report.attempts[0].failures;
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].atom, 0);
+    assert_eq!(failures[0].call.to_string(), "Q(Beta)");
+}
+
+#[test]
+fn test_why_not_each_candidate_binding() {
+    let model = evaluate(
+        "declare Q(Ann); declare Q(Bob); declare B(Cid); \
+         conclusion A(x):-Q(y),B(x),Q(x)",
+    );
+    let report = model.why_not(&fact("A(Cid)")).expect("expected report");
+    let failures = &report.attempts[0].failures;
+    assert_eq!(failures.len(), 2);
+    assert!(failures.iter().all(|failure| failure.atom == 2));
+    assert!(
+        failures
+            .iter()
+            .all(|failure| failure.call.to_string() == "Q(Cid)")
+    );
+}
+
+#[test]
+fn test_why_not_skips_rules_with_other_heads() {
+    let input = "declare Q(Ann); conclusion A(x,Id):-Q(x); conclusion A(x,x):-Q(x)";
+    let report = evaluate(input)
+        .why_not(&fact("A(Bob, Other)"))
+        .expect("expected report");
+    assert!(report.attempts.is_empty());
+    assert!(
+        report
+            .render_text(&LineIndex::new(input))
+            .contains("no conclusion has a head matching")
+    );
+}
+
+#[test]
+fn test_why_not_text_rendering() {
+    let model = evaluate(PROGRAM);
+    let report = model
+        .why_not(&fact("A(Beta, Beta)"))
+        .expect("expected report");
+    assert_eq!(
+        report.render_text(&LineIndex::new(PROGRAM)),
+        "A(Beta, Beta) is not derivable\n  \
+         conclusion A(x, y) :- Q(x), B(y) at 2:12\n    \
+         with x = Beta, y = Beta: fails at body atom 1 Q(x) (2:20): no fact matches Q(Beta)\n"
+    );
+}
+
+const NEGATION: &str = "declare Q(Ann); declare Q(Bob); declare B(Bob);\n\
+    conclusion A(x):-Q(x),not B(x)";
+
+#[test]
+fn test_proof_with_negated_literal() {
+    let model = evaluate(NEGATION);
+    let proof = model.proof(&fact("A(Ann)")).expect("expected proof");
+    assert_eq!(
+        proof.render_text(&LineIndex::new(NEGATION)),
+        "A(Ann) by conclusion A(x) :- Q(x), not B(x) at 2:12 with x = Ann\n  \
+         Q(Ann) declared at 1:1\n  \
+         not B(Ann): not derivable\n"
+    );
+    assert!(
+        proof
+            .to_json(&LineIndex::new(NEGATION))
+            .contains("{\"fact\":\"B(Ann)\",\"absent\":true}")
+    );
+}
+
+#[test]
+fn test_why_not_negated_literal_holds() {
+    let model = evaluate(NEGATION);
+    let report = model.why_not(&fact("A(Bob)")).expect("expected report");
+    assert!(
+        report
+            .render_text(&LineIndex::new(NEGATION))
+            .contains("fails at body atom 2 not B(x) (2:27): B(Bob) holds")
+    );
+}
@@ -0,0 +1,388 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::diagnostic::{Diagnostic, codes};
+use crate::parser::{Call, Declaration, Literal, Polarity, Program, Value, is_anonymous};
+use crate::span::LineIndex;
+use crate::stratify::DependencyGraph;
+use crate::unify::{Substitution, VarSupply, unify_with};
+
+// Ответ на запрос: значения переменных цели
+pub type Solution = BTreeMap<String, Value>;
+
+const DEFAULT_MAX_DEPTH: usize = 1024;
+
+// Клауза: факт 'declare' — клауза с пустым телом, 'conclusion' — правило
+struct Clause {
+    head: Call,
+    body: Vec<Literal>,
+}
+
+// Режим ответа на запросы
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    // Обычная SLD-резолюция с возвратами; зацикливается на леворекурсивных правилах
+    #[default]
+    Sld,
+    // Резолюция с таблицами ответов для каждого шаблона вызова (в духе SLG)
+    Tabled,
+}
+
+pub struct Resolver {
+    clauses: Vec<Clause>,
+    max_depth: usize,
+    mode: Mode,
+    // Ошибка стратификации: отрицание внутри цикла не позволяет отвечать на запросы
+    stratification: Result<(), Diagnostic>,
+    // Строки программы для позиций в сообщениях об ошибках
+    lines: LineIndex,
+}
+
+impl Resolver {
+    pub fn new(program: &Program) -> Self {
+        let clauses = program
+            .declarations
+            .iter()
+            .filter_map(|declaration| match declaration {
+                Declaration::Declare { fact, .. } => Some(Clause {
+                    head: fact.clone(),
+                    body: Vec::new(),
+                }),
+                Declaration::Conclusion { left, right, .. } => Some(Clause {
+                    head: left.clone(),
+                    body: right.clone(),
+                }),
+                Declaration::Query { .. }
+                | Declaration::Vars { .. }
+                | Declaration::Error { .. } => None,
+            })
+            .collect();
+
+        Self {
+            clauses,
+            max_depth: DEFAULT_MAX_DEPTH,
+            mode: Mode::default(),
+            stratification: DependencyGraph::new(program).stratify().map(|_| ()),
+            lines: program.lines.clone(),
+        }
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    // Ограничение глубины вывода: защищает от бесконечной рекурсии
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Одинаковые ответы, полученные разными выводами, возвращаются один раз.
+    // Отрицание 'not K' понимается как отрицание по неудаче: K к моменту проверки
+    // должен быть ground, и для него не должно найтись ни одного ответа.
+    pub fn solve(&self, goal: &Call) -> Result<Vec<Solution>, Diagnostic> {
+        if let Err(error) = &self.stratification {
+            return Err(error.clone());
+        }
+
+        match self.mode {
+            Mode::Sld => self.solve_sld(goal),
+            Mode::Tabled => self.solve_tabled(goal),
+        }
+    }
+
+    // SLD-резолюция: цель раскрывается по клаузам в порядке их объявления,
+    // подцели решаются слева направо, при неудаче происходит возврат (backtracking)
+    fn solve_sld(&self, goal: &Call) -> Result<Vec<Solution>, Diagnostic> {
+        let mut solutions = Vec::new();
+        self.resolve(
+            goal,
+            vec![Literal::positive(goal.clone())],
+            Substitution::new(),
+            0,
+            VarSupply::new(),
+            &mut solutions,
+        )?;
+        Ok(solutions)
+    }
+
+    fn resolve(
+        &self,
+        query: &Call,
+        goals: Vec<Literal>,
+        subst: Substitution,
+        depth: usize,
+        supply: VarSupply,
+        solutions: &mut Vec<Solution>,
+    ) -> Result<(), Diagnostic> {
+        let Some(selected) = select(&goals, &subst, &self.lines)? else {
+            let solution = project(query, &subst);
+            if !solutions.contains(&solution) {
+                solutions.push(solution);
+            }
+            return Ok(());
+        };
+        let mut rest = goals;
+        let Literal {
+            call: first,
+            polarity,
+            ..
+        } = rest.remove(selected);
+
+        if depth >= self.max_depth {
+            return Err(Diagnostic::error(
+                codes::DEPTH_LIMIT_EXCEEDED,
+                format!(
+                    "Depth limit of {} exceeded while resolving {}; the rules may be recursive",
+                    self.max_depth, query
+                ),
+                self.lines.location(query.span),
+            ));
+        }
+
+        if polarity == Polarity::Negative {
+            if self.solve_sld(&subst.apply(&first))?.is_empty() {
+                self.resolve(query, rest, subst, depth + 1, supply, solutions)?;
+            }
+            return Ok(());
+        }
+
+        for clause in &self.clauses {
+            if clause.head.func != first.func || clause.head.args.len() != first.args.len() {
+                continue;
+            }
+
+            let mut supply = supply;
+            let (head, body) = rename(clause, &mut supply);
+            if let Some(unified) = unify_with(&first, &head, subst.clone()) {
+                let mut next_goals = body;
+                next_goals.extend_from_slice(&rest);
+                self.resolve(query, next_goals, unified, depth + 1, supply, solutions)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Таблицы ответов: для каждого варианта вызова (с точностью до имён переменных)
+// хранится множество найденных ответов. Порядок регистрации вызовов сохраняется.
+#[derive(Default)]
+struct Tables {
+    calls: Vec<Call>,
+    index: HashMap<Call, usize>,
+    answers: Vec<BTreeSet<Vec<Value>>>,
+    // Отрицаемые ground-подцели лежат в нижнем страте: их результат (есть ли ответ)
+    // вычисляется один раз и переиспользуется во всех проходах
+    negated: HashMap<Call, bool>,
+}
+
+impl Tables {
+    // Возвращает номер таблицы и признак того, что вызов встретился впервые
+    fn register(&mut self, call: &Call) -> (usize, bool) {
+        let key = variant(call);
+        if let Some(&idx) = self.index.get(&key) {
+            return (idx, false);
+        }
+        let idx = self.calls.len();
+        self.calls.push(key.clone());
+        self.index.insert(key, idx);
+        self.answers.push(BTreeSet::new());
+        (idx, true)
+    }
+}
+
+impl Resolver {
+    // Табличная резолюция. Каждый новый шаблон подцели получает таблицу; повторный вызов
+    // того же шаблона не раскрывается заново, а берёт ответы, уже накопленные в таблице.
+    // Вычисление идёт проходами: в каждом проходе все таблицы заново раскрываются по клаузам
+    // по всем накопленным ответам (наивная итерация, без дельт). Ответы, появившиеся
+    // в таблице позже, потребляются в следующем проходе. Проходы повторяются, пока
+    // ни одна таблица не пополнилась — после этого все таблицы завершены. Для программ
+    // без функциональных символов число шаблонов и ответов конечно, поэтому вычисление
+    // завершается и на леворекурсивных правилах.
+    fn solve_tabled(&self, goal: &Call) -> Result<Vec<Solution>, Diagnostic> {
+        let mut tables = Tables::default();
+        let (root, _) = tables.register(goal);
+
+        loop {
+            let mut changed = false;
+            let mut idx = 0;
+            // Таблицы, зарегистрированные во время прохода, раскрываются в нём же
+            while idx < tables.calls.len() {
+                // Ключ таблицы переименовывается тем же источником, что и клаузы,
+                // чтобы их переменные не совпали
+                let mut supply = VarSupply::new();
+                let call = rename_call(&tables.calls[idx], &mut supply);
+                let mut found = Vec::new();
+                for clause in &self.clauses {
+                    if clause.head.func != call.func || clause.head.args.len() != call.args.len() {
+                        continue;
+                    }
+
+                    let mut supply = supply;
+                    let (head, body) = rename(clause, &mut supply);
+                    if let Some(subst) = unify_with(&call, &head, Substitution::new()) {
+                        changed |=
+                            self.consume(&body, subst, supply, &head, &mut tables, &mut found)?;
+                    }
+                }
+
+                for answer in found {
+                    changed |= tables.answers[idx].insert(answer);
+                }
+                idx += 1;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let mut solutions = Vec::new();
+        for answer in &tables.answers[root] {
+            let answer = rename_answer(goal, answer, &mut VarSupply::new());
+            if let Some(subst) = unify_with(goal, &answer, Substitution::new()) {
+                let solution = project(goal, &subst);
+                if !solutions.contains(&solution) {
+                    solutions.push(solution);
+                }
+            }
+        }
+        Ok(solutions)
+    }
+
+    // Решение тела клаузы по ответам из таблиц подцелей.
+    // Возвращает true, если была зарегистрирована новая таблица.
+    fn consume(
+        &self,
+        goals: &[Literal],
+        subst: Substitution,
+        supply: VarSupply,
+        head: &Call,
+        tables: &mut Tables,
+        found: &mut Vec<Vec<Value>>,
+    ) -> Result<bool, Diagnostic> {
+        let Some(selected) = select(goals, &subst, &self.lines)? else {
+            found.push(subst.apply(head).args);
+            return Ok(false);
+        };
+        let mut rest = goals.to_vec();
+        let literal = rest.remove(selected);
+        let subgoal = subst.apply(&literal.call);
+
+        // Отрицаемая цель лежит в нижнем страте и вычисляется отдельно до конца
+        // со своими таблицами; результат запоминается до конца запроса
+        if literal.is_negative() {
+            let key = variant(&subgoal);
+            let holds = match tables.negated.get(&key) {
+                Some(&holds) => holds,
+                None => {
+                    let holds = !self.solve_tabled(&subgoal)?.is_empty();
+                    tables.negated.insert(key, holds);
+                    holds
+                }
+            };
+            if !holds {
+                return self.consume(&rest, subst, supply, head, tables, found);
+            }
+            return Ok(false);
+        }
+
+        let (idx, mut changed) = tables.register(&subgoal);
+        for answer in tables.answers[idx].clone() {
+            let mut supply = supply;
+            let answer = rename_answer(&subgoal, &answer, &mut supply);
+            if let Some(unified) = unify_with(&subgoal, &answer, subst.clone()) {
+                changed |= self.consume(&rest, unified, supply, head, tables, found)?;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+// Выбор следующего литерала: первый положительный или первый отрицательный,
+// ставший ground. None, если целей не осталось.
+fn select(
+    goals: &[Literal],
+    subst: &Substitution,
+    lines: &LineIndex,
+) -> Result<Option<usize>, Diagnostic> {
+    if goals.is_empty() {
+        return Ok(None);
+    }
+
+    let selected = goals.iter().position(|literal| {
+        !literal.is_negative()
+            || subst
+                .apply(&literal.call)
+                .args
+                .iter()
+                .all(|arg| !matches!(arg, Value::Variable(_)))
+    });
+    match selected {
+        Some(idx) => Ok(Some(idx)),
+        None => Err(Diagnostic::error(
+            codes::NON_GROUND_NEGATION,
+            format!(
+                "Negated literal {} is not ground when selected",
+                subst.apply(&goals[0].call)
+            ),
+            lines.location(goals[0].call.span),
+        )
+        .with_help("bind its variables with a positive literal in the same body")),
+    }
+}
+
+// Ответ из таблицы как вызов; его переменные (если ответ не ground) переименовываются
+fn rename_answer(call: &Call, answer: &[Value], supply: &mut VarSupply) -> Call {
+    let answer = call.with_args(answer.to_vec());
+    rename_call(&answer, supply)
+}
+
+// Каноническая форма вызова: переменные нумеруются в порядке первого появления,
+// так что A(x, y, x) и A(z, x, z) дают один и тот же ключ таблицы
+fn variant(call: &Call) -> Call {
+    rename_call(call, &mut VarSupply::new())
+}
+
+fn rename_call(call: &Call, supply: &mut VarSupply) -> Call {
+    supply.rename_apart(std::slice::from_ref(call)).remove(0)
+}
+
+// Переименование переменных клаузы в свежие, чтобы её можно было применять повторно
+fn rename(clause: &Clause, supply: &mut VarSupply) -> (Call, Vec<Literal>) {
+    let mut calls = Vec::with_capacity(clause.body.len() + 1);
+    calls.push(clause.head.clone());
+    calls.extend(clause.body.iter().map(|literal| literal.call.clone()));
+    let mut renamed = supply.rename_apart(&calls).into_iter();
+    let head = renamed.next().unwrap_or_else(|| clause.head.clone());
+    let body = clause
+        .body
+        .iter()
+        .zip(renamed)
+        .map(|(literal, call)| Literal {
+            call,
+            polarity: literal.polarity,
+            span: literal.span,
+        })
+        .collect();
+    (head, body)
+}
+
+// Значения переменных исходной цели после успешного вывода; анонимные переменные
+// в ответ не входят
+fn project(query: &Call, subst: &Substitution) -> Solution {
+    query
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            Value::Variable(var) if is_anonymous(var) => None,
+            Value::Variable(var) => Some((var.clone(), subst.resolve(arg))),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,280 @@
+use crate::config::{LanguageConfig, Variables};
+use crate::diagnostic::{Location, codes};
+use crate::lexer::Lexer;
+use crate::parser::{Call, Parser, Program, Value};
+use crate::query::{Mode, Resolver, Solution};
+
+fn parse(input: &str) -> Program {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    parser.parse_program().expect("parsing failed")
+}
+
+fn goal(input: &str) -> Call {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    parser.parse_goal().expect("parsing goal failed")
+}
+
+fn solve(program: &str, query: &str) -> Vec<Solution> {
+    let program = parse(program);
+    Resolver::new(&program)
+        .solve(&goal(query))
+        .expect("query failed")
+}
+
+fn id(name: &str) -> Value {
+    Value::Identifier(name.to_string())
+}
+
+#[test]
+fn test_query_fact() {
+    let solutions = solve("declare Q(Alpha); declare Q(Beta)", "Q(x)");
+    assert_eq!(solutions.len(), 2);
+    assert_eq!(solutions[0]["x"], id("Alpha"));
+    assert_eq!(solutions[1]["x"], id("Beta"));
+}
+
+#[test]
+fn test_query_ground_goal() {
+    let program = "declare Q(Alpha)";
+    assert_eq!(solve(program, "Q(Alpha)"), vec![Solution::new()]);
+    assert!(solve(program, "Q(Beta)").is_empty());
+}
+
+#[test]
+fn test_query_through_rule_with_backtracking() {
+    let program = "declare Q(Alpha); declare Q(Beta); declare B(Beta); \
+        conclusion A(x,Name):-Q(x),B(x)";
+    let solutions = solve(program, "A(x, Name)");
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(solutions[0]["x"], id("Beta"));
+}
+
+#[test]
+fn test_query_rule_used_twice() {
+    let program = "declare Q(Alpha); declare Q(Beta); \
+        conclusion A(x,z):-B(x),B(z); \
+        conclusion B(x):-Q(x)";
+    let solutions = solve(program, "A(x, y)");
+    assert_eq!(solutions.len(), 4);
+    assert!(
+        solutions
+            .iter()
+            .any(|s| s["x"] == id("Alpha") && s["y"] == id("Beta"))
+    );
+}
+
+#[test]
+fn test_query_constant_in_rule_head() {
+    let program = "declare Q(Alpha); conclusion A(x,Id):-Q(x)";
+    assert!(solve(program, "A(x, Other)").is_empty());
+    assert_eq!(solve(program, "A(x, y)")[0]["y"], id("Id"));
+}
+
+#[test]
+fn test_query_error_left_recursion_hits_depth_limit() {
+    let program = parse("declare Q(Alpha); conclusion A(x):-A(x); conclusion A(x):-Q(x)");
+    let error = Resolver::new(&program)
+        .max_depth(64)
+        .solve(&goal("A(x)"))
+        .expect_err("expected depth limit error");
+    assert_eq!(error.code, codes::DEPTH_LIMIT_EXCEEDED);
+    assert!(error.message.contains("Depth limit of 64 exceeded"));
+}
+
+fn solve_tabled(program: &str, query: &str) -> Vec<Solution> {
+    let program = parse(program);
+    Resolver::new(&program)
+        .mode(Mode::Tabled)
+        .solve(&goal(query))
+        .expect("tabled query failed")
+}
+
+const CHAIN: &str = "declare Q(Ann); declare Q(Bob); declare Q(Cid); \
+    declare B(Bob); declare B(Cid); declare B(Dan); \
+    conclusion A(x,y):-A(x,z),A(z,y); \
+    conclusion A(x,y):-Q(x),B(y)";
+
+#[test]
+fn test_tabled_left_recursion_terminates() {
+    let solutions = solve_tabled(CHAIN, "A(Ann, y)");
+    let values: Vec<_> = solutions.iter().map(|s| s["y"].clone()).collect();
+    assert_eq!(values, vec![id("Bob"), id("Cid"), id("Dan")]);
+}
+
+#[test]
+fn test_tabled_all_answers_of_recursive_predicate() {
+    let solutions = solve_tabled(CHAIN, "A(x, y)");
+    assert_eq!(solutions.len(), 9);
+}
+
+#[test]
+fn test_tabled_repeated_variable_in_goal() {
+    let solutions = solve_tabled(CHAIN, "A(x, x)");
+    let values: Vec<_> = solutions.iter().map(|s| s["x"].clone()).collect();
+    assert_eq!(values, vec![id("Bob"), id("Cid")]);
+}
+
+#[test]
+fn test_tabled_matches_sld_on_non_recursive_program() {
+    let program = "declare Q(Alpha); declare Q(Beta); declare B(Beta); \
+        conclusion A(x,z):-B(x),Q(z); \
+        conclusion B(x):-Q(x)";
+    let mut sld = solve(program, "A(x, y)");
+    let mut tabled = solve_tabled(program, "A(x, y)");
+    sld.sort();
+    tabled.sort();
+    assert_eq!(sld, tabled);
+}
+
+#[test]
+fn test_tabled_ground_goal() {
+    assert_eq!(solve_tabled(CHAIN, "A(Cid, Dan)"), vec![Solution::new()]);
+    assert!(solve_tabled(CHAIN, "A(Dan, Ann)").is_empty());
+}
+
+#[test]
+fn test_query_declarations_are_not_clauses() {
+    let solutions = solve("declare Q(Alpha); ?- Q(x); query Q(Beta)", "Q(x)");
+    assert_eq!(solutions.len(), 1);
+}
+
+const NEGATION: &str = "declare Q(Ann); declare Q(Bob); declare B(Bob); \
+    conclusion A(x):-not B(x),Q(x)";
+
+#[test]
+fn test_query_negation_as_failure() {
+    for mode in [Mode::Sld, Mode::Tabled] {
+        let program = parse(NEGATION);
+        let solutions = Resolver::new(&program)
+            .mode(mode)
+            .solve(&goal("A(x)"))
+            .expect("query failed");
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0]["x"], id("Ann"));
+    }
+}
+
+#[test]
+fn test_query_error_floundering_negation() {
+    for mode in [Mode::Sld, Mode::Tabled] {
+        let program = parse("declare Q(Ann); conclusion A(x):-Q(x),not B(y)");
+        let error = Resolver::new(&program)
+            .mode(mode)
+            .solve(&goal("A(x)"))
+            .expect_err("expected query error");
+        assert_eq!(error.code, codes::NON_GROUND_NEGATION);
+        assert_eq!(error.location, Location::new(1, 43, 4));
+        assert!(error.message.contains("is not ground"));
+    }
+}
+
+#[test]
+fn test_query_error_unstratifiable_program() {
+    let program = parse("declare Q(Ann); conclusion A(x):-Q(x),not A(x)");
+    let error = Resolver::new(&program)
+        .solve(&goal("A(x)"))
+        .expect_err("expected query error");
+    assert_eq!(error.code, codes::NOT_STRATIFIABLE);
+    assert!(error.message.contains("not stratifiable"));
+}
+
+#[test]
+fn test_query_prolog_style_variables() {
+    let config = LanguageConfig::new()
+        .variables(Variables::PrologStyle)
+        .any_capitalised();
+    let tokens = Lexer::new()
+        .lex("declare Edge(ann); conclusion Path(From, To):-Edge(From), Edge(To)")
+        .expect("lexing failed");
+    let program = Parser::new(tokens, config.clone())
+        .parse_program()
+        .expect("parsing failed");
+    let tokens = Lexer::new().lex("Path(ann, Where)").expect("lexing failed");
+    let goal = Parser::new(tokens, config)
+        .parse_goal()
+        .expect("parsing goal failed");
+
+    let solutions = Resolver::new(&program).solve(&goal).expect("query failed");
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(solutions[0]["Where"], id("ann"));
+}
+
+#[test]
+fn test_query_zero_arity_goal() {
+    let config = LanguageConfig::default().zero_arity(true);
+    let tokens = Lexer::new()
+        .lex("declare Q(Alpha); conclusion B:-Q(x); conclusion A():-Q(Beta)")
+        .expect("lexing failed");
+    let program = Parser::new(tokens, config.clone())
+        .parse_program()
+        .expect("parsing failed");
+    let resolver = Resolver::new(&program);
+    for (query, expected) in [("B", vec![Solution::new()]), ("A()", vec![])] {
+        let tokens = Lexer::new().lex(query).expect("lexing failed");
+        let goal = Parser::new(tokens, config.clone())
+            .parse_goal()
+            .expect("parsing goal failed");
+        assert_eq!(resolver.solve(&goal).expect("query failed"), expected);
+    }
+}
+
+fn solve_prolog(program: &str, query: &str, mode: Mode) -> Vec<Solution> {
+    let config = LanguageConfig::new()
+        .variables(Variables::PrologStyle)
+        .any_capitalised();
+    let tokens = Lexer::new().lex(program).expect("lexing failed");
+    let program = Parser::new(tokens, config.clone())
+        .parse_program()
+        .expect("parsing failed");
+    let tokens = Lexer::new().lex(query).expect("lexing failed");
+    let goal = Parser::new(tokens, config)
+        .parse_goal()
+        .expect("parsing goal failed");
+    Resolver::new(&program)
+        .mode(mode)
+        .solve(&goal)
+        .expect("query failed")
+}
+
+const ANONYMOUS: &str = "declare B(a, one); declare Q(two); conclusion A(X):-B(X,_),Q(_)";
+
+#[test]
+fn test_tabled_negation_inside_left_recursion() {
+    // Отрицаемая подцель проверяется в каждом проходе по таблицам рекурсивного предиката
+    let program = "declare Q(Ann, Bob); declare Q(Bob, Cid); declare Q(Cid, Dan); \
+        declare Q(Dan, Eve); declare B(Cid); \
+        conclusion A(x, y):-A(x, z),Q(z, y),not B(y); \
+        conclusion A(x, y):-Q(x, y),not B(y)";
+    let solutions = solve_tabled(program, "A(Ann, y)");
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(solutions[0]["y"], id("Bob"));
+    let mut solutions = solve_tabled(program, "A(Cid, y)");
+    solutions.sort();
+    assert_eq!(solutions.len(), 2);
+    assert_eq!(solutions[0]["y"], id("Dan"));
+    assert_eq!(solutions[1]["y"], id("Eve"));
+}
+
+#[test]
+fn test_query_anonymous_variables_are_distinct() {
+    // Два '_' в правиле — разные переменные: B(a, one) и Q(two) не обязаны совпадать
+    for mode in [Mode::Sld, Mode::Tabled] {
+        let solutions = solve_prolog(ANONYMOUS, "A(W)", mode);
+        assert_eq!(solutions.len(), 1, "{:?}", mode);
+        assert_eq!(solutions[0]["W"], id("a"));
+    }
+}
+
+#[test]
+fn test_query_anonymous_goal_variable_is_not_projected() {
+    for mode in [Mode::Sld, Mode::Tabled] {
+        assert_eq!(solve_prolog(ANONYMOUS, "A(_)", mode), vec![Solution::new()]);
+        let solutions = solve_prolog(ANONYMOUS, "B(X, _)", mode);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].keys().collect::<Vec<_>>(), vec!["X"]);
+    }
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::diagnostic::{Diagnostic, Location, Severity};
+
+// Табуляция в выводе заменяется на столько пробелов
+const TAB_WIDTH: usize = 4;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+
+// Вывод диагностик в стиле rustc: заголовок, имя файла с позицией,
+// строки исходного текста и подчёркивание под основным (^) и дополнительными (-) фрагментами.
+//
+// error[E0201]: Predicate Q is used with 2 argument(s), but its first use has 1
+//  --> program.txt:2:12
+//   |
+// 1 | declare Q(Name);
+//   |         - first use of Q with 1 argument(s)
+// 2 | conclusion Q(x,y):-B(z)
+//   |            ^
+pub struct Renderer<'a> {
+    filename: &'a str,
+    lines: Vec<&'a str>,
+    colour: bool,
+}
+
+// Подчёркивание одного фрагмента строки
+struct Annotation<'d> {
+    location: Location,
+    primary: bool,
+    message: Option<&'d str>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(filename: &'a str, source: &'a str) -> Self {
+        Self {
+            filename,
+            lines: source.lines().collect(),
+            colour: true,
+        }
+    }
+
+    // Без ANSI-последовательностей: для логов и перенаправленного вывода
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.colour = !plain;
+        self
+    }
+
+    pub fn render_all(&self, diagnostics: &[Diagnostic]) -> String {
+        diagnostics
+            .iter()
+            .map(|diagnostic| self.render(diagnostic))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let severity_colour = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => CYAN,
+        };
+
+        let _ = writeln!(
+            out,
+            "{}{}[{}]{}{}: {}{}",
+            self.paint(severity_colour),
+            diagnostic.severity,
+            diagnostic.code,
+            self.paint(RESET),
+            self.paint(BOLD),
+            diagnostic.message,
+            self.paint(RESET)
+        );
+
+        // Подпись к тому же фрагменту, что и основной, выводится рядом с '^'
+        let labels = &diagnostic.labels;
+        let merged = labels
+            .iter()
+            .position(|label| label.location == diagnostic.location);
+        let mut annotations = vec![Annotation {
+            location: diagnostic.location,
+            primary: true,
+            message: merged.map(|idx| labels[idx].message.as_str()),
+        }];
+        let others = (0..labels.len())
+            .filter(|&idx| Some(idx) != merged)
+            .map(|idx| &labels[idx]);
+        annotations.extend(others.map(|label| Annotation {
+            location: label.location,
+            primary: false,
+            message: Some(label.message.as_str()),
+        }));
+        // Фрагменты без позиции (построенные программно) не показываются
+        annotations.retain(|annotation| annotation.location.line > 0);
+        annotations
+            .sort_by_key(|annotation| (annotation.location.line, annotation.location.column));
+
+        let gutter = annotations
+            .iter()
+            .map(|annotation| annotation.location.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let pad = " ".repeat(gutter);
+
+        // Ошибка без позиции (например, расхождение стратегий вычисления)
+        // относится к файлу целиком
+        let position = match diagnostic.location.line {
+            0 => String::new(),
+            line => format!(":{}:{}", line, diagnostic.location.column),
+        };
+        let _ = writeln!(
+            out,
+            "{}{}-->{} {}{}",
+            pad,
+            self.paint(BLUE),
+            self.paint(RESET),
+            self.filename,
+            position
+        );
+
+        if !annotations.is_empty() {
+            let _ = writeln!(out, "{} {}|{}", pad, self.paint(BLUE), self.paint(RESET));
+        }
+
+        let mut previous: Option<usize> = None;
+        for annotation in &annotations {
+            let line = annotation.location.line;
+            if previous != Some(line) {
+                if previous.is_some_and(|previous| line > previous + 1) {
+                    let _ = writeln!(out, "{}...{}", self.paint(BLUE), self.paint(RESET));
+                }
+                let text = self.line_text(line);
+                let _ = writeln!(
+                    out,
+                    "{}{:>gutter$} |{} {}",
+                    self.paint(BLUE),
+                    line,
+                    self.paint(RESET),
+                    expand_tabs(text).trim_end(),
+                );
+                previous = Some(line);
+            }
+
+            let text = self.line_text(line);
+            let (offset, width) = underline(text, annotation.location);
+            let (mark, colour) = if annotation.primary {
+                ('^', severity_colour)
+            } else {
+                ('-', BLUE)
+            };
+            let _ = write!(
+                out,
+                "{} {}|{} {}{}{}",
+                pad,
+                self.paint(BLUE),
+                self.paint(RESET),
+                " ".repeat(offset),
+                self.paint(colour),
+                mark.to_string().repeat(width)
+            );
+            if let Some(message) = annotation.message {
+                let _ = write!(out, " {}", message);
+            }
+            let _ = writeln!(out, "{}", self.paint(RESET));
+        }
+
+        if let Some(help) = &diagnostic.help {
+            let _ = writeln!(
+                out,
+                "{} {}={} {}help{}: {}",
+                pad,
+                self.paint(BLUE),
+                self.paint(RESET),
+                self.paint(BOLD),
+                self.paint(RESET),
+                help
+            );
+        }
+        out
+    }
+
+    fn line_text(&self, line: usize) -> &str {
+        self.lines.get(line.wrapping_sub(1)).copied().unwrap_or("")
+    }
+
+    fn paint(&self, code: &'static str) -> &'static str {
+        if self.colour { code } else { "" }
+    }
+}
+
+// Ширина символа на экране; табуляция раскрывается в TAB_WIDTH пробелов
+fn char_width(ch: char) -> usize {
+    if ch == '\t' {
+        TAB_WIDTH
+    } else {
+        ch.width().unwrap_or(0)
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+// Отступ и ширина подчёркивания в экранных столбцах. Столбцы location считаются
+// в символах, поэтому многобайтовые и широкие символы учитываются по ширине.
+// Подчёркивание не бывает короче одного столбца (например, для конца файла).
+fn underline(text: &str, location: Location) -> (usize, usize) {
+    let start = location.column.saturating_sub(1);
+    let chars: Vec<char> = text.chars().collect();
+
+    let offset: usize = chars.iter().take(start).map(|&ch| char_width(ch)).sum();
+    // Позиция за концом строки: недостающие символы считаются пробелами
+    let offset = offset + start.saturating_sub(chars.len());
+    let width: usize = chars
+        .iter()
+        .skip(start)
+        .take(location.len)
+        .map(|&ch| char_width(ch))
+        .sum();
+    (offset, width.max(1))
+}
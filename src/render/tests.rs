@@ -0,0 +1,127 @@
+use crate::diagnostic::{Diagnostic, Location, codes};
+use crate::render::Renderer;
+
+fn error(line: usize, column: usize, len: usize) -> Diagnostic {
+    Diagnostic::error(
+        codes::EXPECTED_TOKEN,
+        "Expected '-' after ':'",
+        Location::new(line, column, len),
+    )
+}
+
+#[test]
+fn test_render_plain_snippet_with_caret() {
+    let source = "declare Q(Name);\nconclusion Q(x):B(y)";
+    let rendered = Renderer::new("program.txt", source)
+        .plain(true)
+        .render(&error(2, 17, 1));
+    assert_eq!(
+        rendered,
+        "error[E0101]: Expected '-' after ':'\n \
+         --> program.txt:2:17\n  \
+         |\n\
+         2 | conclusion Q(x):B(y)\n  \
+         |                 ^\n"
+    );
+}
+
+#[test]
+fn test_render_secondary_label_and_help() {
+    let source = "declare Q(Name);\n\n\nconclusion Q(x,y):-B(z)";
+    let diagnostic = Diagnostic::error(
+        codes::ARITY_MISMATCH,
+        "Arity mismatch",
+        Location::new(4, 12, 1),
+    )
+    .with_label(Location::new(1, 1, 7), "first use")
+    .with_help("use one arity");
+    let rendered = Renderer::new("kb.txt", source)
+        .plain(true)
+        .render(&diagnostic);
+    assert_eq!(
+        rendered,
+        "error[E0201]: Arity mismatch\n \
+         --> kb.txt:4:12\n  \
+         |\n\
+         1 | declare Q(Name);\n  \
+         | ------- first use\n\
+         ...\n\
+         4 | conclusion Q(x,y):-B(z)\n  \
+         |            ^\n  \
+         = help: use one arity\n"
+    );
+}
+
+#[test]
+fn test_render_label_on_primary_span_shares_caret_line() {
+    let source = "conclusion A(x):-Q(x),not A(x)";
+    let diagnostic = Diagnostic::error(
+        codes::NOT_STRATIFIABLE,
+        "Not stratifiable",
+        Location::new(1, 27, 1),
+    )
+    .with_label(Location::new(1, 27, 1), "A depends on not A");
+    let rendered = Renderer::new("kb.txt", source)
+        .plain(true)
+        .render(&diagnostic);
+    assert_eq!(
+        rendered,
+        "error[E0203]: Not stratifiable\n \
+         --> kb.txt:1:27\n  \
+         |\n\
+         1 | conclusion A(x):-Q(x),not A(x)\n  \
+         |                           ^ A depends on not A\n"
+    );
+}
+
+#[test]
+fn test_render_tabs_are_expanded() {
+    let source = "\tconclusion Q(x):B(y)";
+    let rendered = Renderer::new("f", source)
+        .plain(true)
+        .render(&error(1, 17, 1));
+    let lines: Vec<_> = rendered.lines().collect();
+    assert_eq!(lines[3], "1 |     conclusion Q(x):B(y)");
+    assert_eq!(lines[4], "  |                    ^");
+}
+
+#[test]
+fn test_render_multibyte_and_wide_characters() {
+    // Столбцы считаются в символах; 'Ж' занимает один столбец экрана, '語' — два
+    let source = "Ж語 Q(x)";
+    let rendered = Renderer::new("f", source)
+        .plain(true)
+        .render(&error(1, 4, 4));
+    let lines: Vec<_> = rendered.lines().collect();
+    assert_eq!(lines[4], "  |     ^^^^");
+}
+
+#[test]
+fn test_render_end_of_file_points_past_line() {
+    let rendered = Renderer::new("f", "declare Q(Name")
+        .plain(true)
+        .render(&error(1, 15, 0));
+    let lines: Vec<_> = rendered.lines().collect();
+    assert_eq!(lines[4], "  |               ^");
+}
+
+#[test]
+fn test_render_without_location_names_only_file() {
+    let rendered = Renderer::new("kb.txt", "declare Q(Ann);")
+        .plain(true)
+        .render(&error(0, 0, 0));
+    assert_eq!(
+        rendered,
+        "error[E0101]: Expected '-' after ':'\n --> kb.txt\n"
+    );
+}
+
+#[test]
+fn test_render_colour_mode_uses_ansi_codes() {
+    let coloured = Renderer::new("f", "declare Q(").render(&error(1, 11, 0));
+    assert!(coloured.contains("\x1b[1;31merror[E0101]"));
+    let plain = Renderer::new("f", "declare Q(")
+        .plain(true)
+        .render(&error(1, 11, 0));
+    assert!(!plain.contains('\x1b'));
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests;
+
+use crate::diagnostic::Location;
+
+// Фрагмент исходного текста в байтах: [start, end)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    // Наименьший фрагмент, содержащий оба
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    // Текст фрагмента; None, если фрагмент выходит за пределы source
+    // или его границы не совпадают с границами символов
+    pub fn text(self, source: &str) -> Option<&str> {
+        source.get(self.start..self.end)
+    }
+}
+
+// Начала строк исходного текста для перевода смещений в строку и столбец.
+// Позиции в программе хранятся только как Span; строка и столбец вычисляются здесь.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LineIndex {
+    source: String,
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            source: source.to_string(),
+            line_starts,
+        }
+    }
+
+    // Строка и столбец (с 1) для смещения; столбец считается в символах.
+    // Смещение за концом текста или внутри символа сдвигается к ближайшей
+    // предшествующей границе символа.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clamp(offset);
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let column = self.source[start..offset].chars().count() + 1;
+        (line + 1, column)
+    }
+
+    // Смещение начала строки line (с 1)
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line.checked_sub(1)?).copied()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    // Место фрагмента для диагностики; границы фрагмента сдвигаются, как в line_col
+    pub fn location(&self, span: Span) -> Location {
+        let (start, end) = (self.clamp(span.start), self.clamp(span.end));
+        let (line, column) = self.line_col(start);
+        let len = self.source[start..end.max(start)].chars().count();
+        Location::new(line, column, len)
+    }
+
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
@@ -0,0 +1,50 @@
+use crate::diagnostic::Location;
+use crate::span::{LineIndex, Span};
+
+#[test]
+fn test_span_join_and_text() {
+    let source = "declare Q(Ann)";
+    let keyword = Span::new(0, 7);
+    let close = Span::new(13, 14);
+    let whole = keyword.to(close);
+    assert_eq!(whole, Span::new(0, 14));
+    assert_eq!(whole.text(source), Some(source));
+    assert_eq!(Span::new(10, 40).text(source), None);
+    assert_eq!(keyword.len(), 7);
+    assert!(Span::default().is_empty());
+}
+
+#[test]
+fn test_line_index_line_col() {
+    let index = LineIndex::new("declare Q(Ann);\ndeclare B(Bob)\n");
+    assert_eq!(index.line_count(), 3);
+    assert_eq!(index.line_col(0), (1, 1));
+    assert_eq!(index.line_col(8), (1, 9));
+    assert_eq!(index.line_col(15), (1, 16));
+    assert_eq!(index.line_col(16), (2, 1));
+    assert_eq!(index.line_col(24), (2, 9));
+    assert_eq!(index.line_col(31), (3, 1));
+    assert_eq!(index.line_start(2), Some(16));
+    assert_eq!(index.line_start(0), None);
+}
+
+#[test]
+fn test_line_index_counts_columns_in_chars() {
+    let source = "declare Q(Ёж); B";
+    let index = LineIndex::new(source);
+    let offset = source.find('B').unwrap();
+    assert_eq!(index.line_col(offset), (1, 16));
+    let hedgehog = Span::new(10, 10 + "Ёж".len());
+    assert_eq!(index.location(hedgehog), Location::new(1, 11, 2));
+}
+
+#[test]
+fn test_line_index_clamps_offsets() {
+    let source = "Q(Ёж)\nB";
+    let index = LineIndex::new(source);
+    // Смещение внутри 'Ё' относится к началу символа
+    assert_eq!(index.line_col(3), (1, 3));
+    assert_eq!(index.line_col(100), (2, 2));
+    assert_eq!(index.location(Span::new(2, 100)), Location::new(1, 3, 5));
+    assert_eq!(Span::new(3, 5).text(source), None);
+}
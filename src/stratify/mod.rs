@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::diagnostic::{Diagnostic, Location, codes};
+use crate::parser::{Declaration, Program};
+use crate::span::{LineIndex, Span};
+
+// Ребро графа зависимостей: предикат заключения from зависит от предиката to из тела.
+// span — байты вызова литерала в теле правила.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub from: String,
+    pub to: String,
+    pub negative: bool,
+    pub span: Span,
+}
+
+impl Dependency {
+    // Место имени предиката to в теле правила
+    fn location(&self, lines: &LineIndex) -> Location {
+        let (line, column) = lines.line_col(self.span.start);
+        Location::new(line, column, self.to.chars().count())
+    }
+}
+
+// Граф зависимостей предикатов программы
+pub struct DependencyGraph<'p> {
+    predicates: BTreeSet<String>,
+    edges: Vec<Dependency>,
+    // Строки программы для позиций рёбер в сообщениях
+    lines: &'p LineIndex,
+}
+
+impl<'p> DependencyGraph<'p> {
+    pub fn new(program: &'p Program) -> Self {
+        let mut predicates = BTreeSet::new();
+        let mut edges = Vec::new();
+
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Declare { fact, .. } => {
+                    predicates.insert(fact.func.clone());
+                }
+                Declaration::Conclusion { left, right, .. } => {
+                    predicates.insert(left.func.clone());
+                    for literal in right {
+                        predicates.insert(literal.call.func.clone());
+                        edges.push(Dependency {
+                            from: left.func.clone(),
+                            to: literal.call.func.clone(),
+                            negative: literal.is_negative(),
+                            span: literal.call.span,
+                        });
+                    }
+                }
+                Declaration::Query { .. }
+                | Declaration::Vars { .. }
+                | Declaration::Error { .. } => {}
+            }
+        }
+
+        Self {
+            predicates,
+            edges,
+            lines: &program.lines,
+        }
+    }
+
+    pub fn edges(&self) -> &[Dependency] {
+        &self.edges
+    }
+
+    // Разбиение предикатов на страты: предикат попадает в страт не ниже стратов,
+    // от которых зависит, и строго выше стратов, от которых зависит через отрицание.
+    // Ошибка, если отрицание встречается внутри цикла: диагностика указывает на
+    // отрицаемый литерал, с которого начинается цикл, и подписывает каждое его ребро.
+    pub fn stratify(&self) -> Result<Vec<BTreeSet<String>>, Diagnostic> {
+        let components = self.components();
+        let component_of: BTreeMap<&str, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, members)| members.iter().map(move |p| (p.as_str(), idx)))
+            .collect();
+
+        for edge in &self.edges {
+            if edge.negative && component_of[edge.from.as_str()] == component_of[edge.to.as_str()] {
+                let cycle = self.cycle_through(edge);
+                return Err(self.cycle_diagnostic(&cycle));
+            }
+        }
+
+        // Компоненты идут в обратном топологическом порядке (зависимости раньше),
+        // поэтому номер страта вычисляется за один проход
+        let mut stratum_of = vec![0usize; components.len()];
+        for (idx, members) in components.iter().enumerate() {
+            for edge in self.edges.iter().filter(|e| members.contains(&e.from)) {
+                let dep = component_of[edge.to.as_str()];
+                if dep != idx {
+                    stratum_of[idx] = stratum_of[idx].max(stratum_of[dep] + edge.negative as usize);
+                }
+            }
+        }
+
+        let count = stratum_of.iter().max().map_or(0, |max| max + 1);
+        let mut strata = vec![BTreeSet::new(); count];
+        for (idx, members) in components.into_iter().enumerate() {
+            strata[stratum_of[idx]].extend(members);
+        }
+        Ok(strata)
+    }
+
+    // Компоненты сильной связности (алгоритм Тарьяна).
+    // Компонента добавляется после всех компонент, достижимых из неё.
+    fn components(&self) -> Vec<BTreeSet<String>> {
+        struct State<'g> {
+            graph: &'g DependencyGraph<'g>,
+            index: BTreeMap<&'g str, usize>,
+            low: BTreeMap<&'g str, usize>,
+            stack: Vec<&'g str>,
+            on_stack: BTreeSet<&'g str>,
+            components: Vec<BTreeSet<String>>,
+        }
+
+        fn visit<'g>(state: &mut State<'g>, node: &'g str) {
+            let idx = state.index.len();
+            state.index.insert(node, idx);
+            state.low.insert(node, idx);
+            state.stack.push(node);
+            state.on_stack.insert(node);
+
+            let graph = state.graph;
+            for edge in graph.edges.iter().filter(|e| e.from == node) {
+                let next = edge.to.as_str();
+                if !state.index.contains_key(next) {
+                    visit(state, next);
+                    let low = state.low[node].min(state.low[next]);
+                    state.low.insert(node, low);
+                } else if state.on_stack.contains(next) {
+                    let low = state.low[node].min(state.index[next]);
+                    state.low.insert(node, low);
+                }
+            }
+
+            if state.low[node] == state.index[node] {
+                let mut component = BTreeSet::new();
+                while let Some(member) = state.stack.pop() {
+                    state.on_stack.remove(member);
+                    component.insert(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                state.components.push(component);
+            }
+        }
+
+        let mut state = State {
+            graph: self,
+            index: BTreeMap::new(),
+            low: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+        for node in &self.predicates {
+            if !state.index.contains_key(node.as_str()) {
+                visit(&mut state, node);
+            }
+        }
+        state.components
+    }
+
+    // Цикл, начинающийся с ребра edge и возвращающийся в edge.from (поиск в ширину)
+    fn cycle_through(&self, edge: &Dependency) -> Vec<Dependency> {
+        let mut previous: BTreeMap<&str, &Dependency> = BTreeMap::new();
+        let mut queue = std::collections::VecDeque::from([edge.to.as_str()]);
+        let mut seen = BTreeSet::from([edge.to.as_str()]);
+
+        while let Some(node) = queue.pop_front() {
+            if node == edge.from {
+                break;
+            }
+            for next in self.edges.iter().filter(|e| e.from == node) {
+                if seen.insert(next.to.as_str()) {
+                    previous.insert(next.to.as_str(), next);
+                    queue.push_back(next.to.as_str());
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut node = edge.from.as_str();
+        while node != edge.to {
+            let step = previous[node];
+            path.push(step.clone());
+            node = step.from.as_str();
+        }
+        path.push(edge.clone());
+        path.reverse();
+        path
+    }
+
+    fn cycle_diagnostic(&self, cycle: &[Dependency]) -> Diagnostic {
+        let (location, func) = match cycle.first() {
+            Some(edge) => (edge.location(self.lines), edge.from.as_str()),
+            None => (Location::default(), ""),
+        };
+
+        cycle
+            .iter()
+            .fold(
+                Diagnostic::error(
+                    codes::NOT_STRATIFIABLE,
+                    format!(
+                        "Program is not stratifiable: {} depends on itself through negation",
+                        func
+                    ),
+                    location,
+                ),
+                |diagnostic, edge| {
+                    let negation = if edge.negative { "not " } else { "" };
+                    diagnostic.with_label(
+                        edge.location(self.lines),
+                        format!("{} depends on {}{}", edge.from, negation, edge.to),
+                    )
+                },
+            )
+            .with_help("use --well-founded to evaluate negation inside recursion")
+    }
+}
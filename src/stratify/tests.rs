@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+
+use crate::config::LanguageConfig;
+use crate::diagnostic::{Location, codes};
+use crate::lexer::Lexer;
+use crate::parser::{Parser, Program};
+use crate::stratify::DependencyGraph;
+
+fn parse(input: &str) -> Program {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.lex(input).expect("lexing failed");
+    let mut parser = Parser::new(tokens, LanguageConfig::default());
+    parser.parse_program().expect("parsing failed")
+}
+
+fn set(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_stratify_without_negation_is_single_stratum() {
+    let program = parse("declare Q(Ann); conclusion A(x):-Q(x); conclusion B(x):-A(x)");
+    let strata = DependencyGraph::new(&program)
+        .stratify()
+        .expect("expected strata");
+    assert_eq!(strata, vec![set(&["A", "B", "Q"])]);
+}
+
+#[test]
+fn test_stratify_negation_raises_stratum() {
+    let program = parse(
+        "declare Q(Ann); conclusion B(x):-Q(x); \
+         conclusion A(x):-Q(x),not B(x)",
+    );
+    let strata = DependencyGraph::new(&program)
+        .stratify()
+        .expect("expected strata");
+    assert_eq!(strata, vec![set(&["B", "Q"]), set(&["A"])]);
+}
+
+#[test]
+fn test_stratify_recursion_through_positive_edges() {
+    let program = parse(
+        "declare Q(Ann); conclusion A(x):-B(x); conclusion B(x):-A(x); \
+         conclusion B(x):-Q(x),not Q(x)",
+    );
+    let strata = DependencyGraph::new(&program)
+        .stratify()
+        .expect("expected strata");
+    assert_eq!(strata, vec![set(&["Q"]), set(&["A", "B"])]);
+}
+
+#[test]
+fn test_stratify_error_self_negation() {
+    let program = parse("declare Q(Ann); conclusion A(x):-Q(x),not A(x)");
+    let error = DependencyGraph::new(&program)
+        .stratify()
+        .expect_err("expected stratification error");
+    assert_eq!(error.location, Location::new(1, 43, 1));
+    assert_eq!(error.labels.len(), 1);
+    assert_eq!(error.labels[0].message, "A depends on not A");
+}
+
+#[test]
+fn test_stratify_error_negation_in_cycle() {
+    let program = parse("conclusion A(x):-Q(x),not B(x);\nconclusion B(x):-A(x)");
+    let diagnostic = DependencyGraph::new(&program)
+        .stratify()
+        .expect_err("expected stratification error");
+    assert_eq!(diagnostic.code, codes::NOT_STRATIFIABLE);
+    assert_eq!(
+        diagnostic.message,
+        "Program is not stratifiable: A depends on itself through negation"
+    );
+    assert_eq!(diagnostic.location, Location::new(1, 27, 1));
+    let labels: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| (label.location, label.message.as_str()))
+        .collect();
+    assert_eq!(
+        labels,
+        vec![
+            (Location::new(1, 27, 1), "A depends on not B"),
+            (Location::new(2, 18, 1), "B depends on A"),
+        ]
+    );
+}
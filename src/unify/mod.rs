@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::parser::{Call, Value};
+
+// Свежие переменные называются ?1, ?2, ...: '?' не может входить в слово,
+// поэтому они не пересекаются с переменными программы
+const FRESH_VAR_PREFIX: char = '?';
+
+// Подстановка: отображение переменных в значения.
+// unify поддерживает её идемпотентной: значения не содержат переменных,
+// связанных этой же подстановкой, поэтому apply достаточно одного шага.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Substitution {
+    bindings: BTreeMap<String, Value>,
+}
+
+impl Substitution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    pub fn get(&self, var: &str) -> Option<&Value> {
+        self.bindings.get(var)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.bindings
+            .iter()
+            .map(|(var, value)| (var.as_str(), value))
+    }
+
+    // Привязка без проверок; вызывающий отвечает за сохранение идемпотентности
+    pub fn bind(&mut self, var: impl Into<String>, value: Value) {
+        self.bindings.insert(var.into(), value);
+    }
+
+    pub fn resolve(&self, value: &Value) -> Value {
+        match value {
+            Value::Variable(var) => self.bindings.get(var).unwrap_or(value).clone(),
+            constant => constant.clone(),
+        }
+    }
+
+    pub fn apply(&self, call: &Call) -> Call {
+        call.with_args(call.args.iter().map(|arg| self.resolve(arg)).collect())
+    }
+
+    // Композиция: self.compose(other).apply(c) == other.apply(&self.apply(c))
+    pub fn compose(&self, other: &Substitution) -> Substitution {
+        let mut bindings = BTreeMap::new();
+        for (var, value) in &self.bindings {
+            bindings.insert(var.clone(), other.resolve(value));
+        }
+        for (var, value) in &other.bindings {
+            bindings.entry(var.clone()).or_insert_with(|| value.clone());
+        }
+        bindings.retain(|var, value| !matches!(value, Value::Variable(v) if v == var));
+        Substitution { bindings }
+    }
+
+    // Привязка с сохранением идемпотентности: value уже разыменовано,
+    // а прежние значения, ссылавшиеся на var, заменяются на value
+    fn extend(&mut self, var: String, value: Value) {
+        for bound in self.bindings.values_mut() {
+            if matches!(bound, Value::Variable(v) if *v == var) {
+                *bound = value.clone();
+            }
+        }
+        self.bindings.insert(var, value);
+    }
+}
+
+// Наиболее общий унификатор двух вызовов
+pub fn unify(left: &Call, right: &Call) -> Option<Substitution> {
+    unify_with(left, right, Substitution::new())
+}
+
+// Унификация с расширением уже имеющейся подстановки
+pub fn unify_with(left: &Call, right: &Call, mut subst: Substitution) -> Option<Substitution> {
+    if left.func != right.func || left.args.len() != right.args.len() {
+        return None;
+    }
+
+    for (a, b) in left.args.iter().zip(&right.args) {
+        match (subst.resolve(a), subst.resolve(b)) {
+            (Value::Variable(x), Value::Variable(y)) if x == y => {}
+            (Value::Variable(var), value) | (value, Value::Variable(var)) => {
+                subst.extend(var, value);
+            }
+            (a, b) => {
+                if a != b {
+                    return None;
+                }
+            }
+        }
+    }
+    Some(subst)
+}
+
+// Источник свежих переменных. Копируется по значению, поэтому при возврате
+// (backtracking) достаточно продолжить со старой копией.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarSupply {
+    next: usize,
+}
+
+impl Default for VarSupply {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VarSupply {
+    pub fn new() -> Self {
+        Self { next: 1 }
+    }
+
+    pub fn fresh(&mut self) -> String {
+        let var = format!("{}{}", FRESH_VAR_PREFIX, self.next);
+        self.next += 1;
+        var
+    }
+
+    // Переименование переменных группы вызовов (например, головы и тела правила)
+    // в свежие: одинаковые переменные получают одно и то же новое имя
+    pub fn rename_apart(&mut self, calls: &[Call]) -> Vec<Call> {
+        let mut renaming = HashMap::new();
+        let mut renamed = Vec::with_capacity(calls.len());
+        for call in calls {
+            let mut args = Vec::with_capacity(call.args.len());
+            for arg in &call.args {
+                args.push(match arg {
+                    Value::Variable(var) => Value::Variable(
+                        renaming
+                            .entry(var.clone())
+                            .or_insert_with(|| self.fresh())
+                            .clone(),
+                    ),
+                    constant => constant.clone(),
+                });
+            }
+            renamed.push(call.with_args(args));
+        }
+        renamed
+    }
+}
@@ -0,0 +1,175 @@
+use proptest::prelude::*;
+
+use crate::parser::{Call, Value};
+use crate::unify::{Substitution, VarSupply, unify};
+
+fn var(name: &str) -> Value {
+    Value::Variable(name.to_string())
+}
+
+fn id(name: &str) -> Value {
+    Value::Identifier(name.to_string())
+}
+
+fn call(func: &str, args: Vec<Value>) -> Call {
+    Call::new(func, args)
+}
+
+fn value_strategy() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        prop::sample::select(vec!["x", "y", "z"]).prop_map(var),
+        prop::sample::select(vec!["Alpha", "Beta", "Gamma"]).prop_map(id),
+    ]
+}
+
+// Пара вызовов одного предиката и одной арности
+fn call_pair_strategy() -> impl Strategy<Value = (Call, Call)> {
+    (1usize..5).prop_flat_map(|arity| {
+        (
+            prop::collection::vec(value_strategy(), arity),
+            prop::collection::vec(value_strategy(), arity),
+        )
+            .prop_map(|(left, right)| (call("A", left), call("A", right)))
+    })
+}
+
+// Произвольная ground-подстановка для x, y, z
+fn ground_strategy() -> impl Strategy<Value = Substitution> {
+    prop::collection::vec(prop::sample::select(vec!["Alpha", "Beta", "Gamma"]), 3).prop_map(
+        |names| {
+            let mut subst = Substitution::new();
+            for (var, name) in ["x", "y", "z"].into_iter().zip(names) {
+                subst.bind(var, id(name));
+            }
+            subst
+        },
+    )
+}
+
+// Подстановка без циклов: переменная связывается только с константой
+// или с переменной, стоящей дальше в порядке x, y, z
+fn acyclic_strategy() -> impl Strategy<Value = Substitution> {
+    prop::collection::vec(prop::option::of(value_strategy()), 3).prop_map(|values| {
+        let mut subst = Substitution::new();
+        for (i, (var, value)) in ["x", "y", "z"].into_iter().zip(values).enumerate() {
+            match value {
+                Some(Value::Variable(other)) if ["x", "y", "z"][..=i].contains(&other.as_str()) => {
+                }
+                Some(value) => subst.bind(var, value),
+                None => {}
+            }
+        }
+        subst
+    })
+}
+
+#[test]
+fn test_unify_binds_variables_both_ways() {
+    let subst = unify(
+        &call("A", vec![var("x"), id("Beta")]),
+        &call("A", vec![id("Alpha"), var("y")]),
+    )
+    .expect("expected unifier");
+    assert_eq!(subst.get("x"), Some(&id("Alpha")));
+    assert_eq!(subst.get("y"), Some(&id("Beta")));
+}
+
+#[test]
+fn test_unify_fails_on_constant_clash() {
+    assert!(unify(&call("Q", vec![id("Alpha")]), &call("Q", vec![id("Beta")])).is_none());
+}
+
+#[test]
+fn test_unify_fails_on_different_predicates_or_arity() {
+    assert!(unify(&call("Q", vec![var("x")]), &call("B", vec![var("x")])).is_none());
+    assert!(
+        unify(
+            &call("A", vec![var("x")]),
+            &call("A", vec![var("x"), var("y")])
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn test_unify_repeated_variable() {
+    let left = call("A", vec![var("x"), var("x")]);
+    assert!(unify(&left, &call("A", vec![id("Alpha"), id("Beta")])).is_none());
+    let subst = unify(&left, &call("A", vec![var("y"), id("Beta")])).expect("expected unifier");
+    assert_eq!(subst.resolve(&var("y")), id("Beta"));
+}
+
+#[test]
+fn test_rename_apart_keeps_sharing() {
+    let calls = vec![
+        call("A", vec![var("x"), var("y")]),
+        call("B", vec![var("y"), id("Name")]),
+    ];
+    let renamed = VarSupply::new().rename_apart(&calls);
+    assert_ne!(renamed[0].args[0], var("x"));
+    assert_eq!(renamed[0].args[1], renamed[1].args[0]);
+    assert_eq!(renamed[1].args[1], id("Name"));
+}
+
+proptest! {
+    #[test]
+    fn prop_unifier_makes_calls_equal((left, right) in call_pair_strategy()) {
+        if let Some(subst) = unify(&left, &right) {
+            prop_assert_eq!(subst.apply(&left), subst.apply(&right));
+        }
+    }
+
+    #[test]
+    fn prop_apply_is_idempotent((left, right) in call_pair_strategy()) {
+        if let Some(subst) = unify(&left, &right) {
+            let once = subst.apply(&left);
+            prop_assert_eq!(subst.apply(&once), once);
+        }
+    }
+
+    // Любой ground-унификатор θ пропускается через mgu σ: θ(σ(t)) == θ(t)
+    #[test]
+    fn prop_unifier_is_most_general(
+        (left, right) in call_pair_strategy(),
+        theta in ground_strategy(),
+    ) {
+        if theta.apply(&left) == theta.apply(&right) {
+            let subst = unify(&left, &right);
+            prop_assert!(subst.is_some());
+            let subst = subst.unwrap();
+            prop_assert_eq!(theta.apply(&subst.apply(&left)), theta.apply(&left));
+            prop_assert_eq!(theta.apply(&subst.apply(&right)), theta.apply(&right));
+        }
+    }
+
+    #[test]
+    fn prop_compose_applies_in_sequence(
+        (left, _) in call_pair_strategy(),
+        first in acyclic_strategy(),
+        second in acyclic_strategy(),
+    ) {
+        let composed = first.compose(&second);
+        prop_assert_eq!(composed.apply(&left), second.apply(&first.apply(&left)));
+    }
+
+    #[test]
+    fn prop_renamed_call_is_variant((left, _) in call_pair_strategy()) {
+        let renamed = VarSupply::new()
+            .rename_apart(std::slice::from_ref(&left))
+            .remove(0);
+        for arg in &renamed.args {
+            if let Value::Variable(name) = arg {
+                prop_assert!(!["x", "y", "z"].contains(&name.as_str()));
+            }
+        }
+        // Вариант унифицируется с оригиналом, связывая каждую переменную ровно один раз
+        let distinct: std::collections::BTreeSet<_> = left
+            .args
+            .iter()
+            .filter(|arg| matches!(arg, Value::Variable(_)))
+            .collect();
+        let subst = unify(&left, &renamed);
+        prop_assert!(subst.is_some());
+        prop_assert_eq!(subst.unwrap().len(), distinct.len());
+    }
+}